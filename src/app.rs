@@ -2,7 +2,7 @@ use anyhow::Result;
 use crossterm::event::{self, Event};
 use ratatui::{backend::Backend, Terminal};
 
-use crate::{navigation::Navigator, config::Config};
+use crate::{navigation::Navigator, config::Config, zfs::format_bytes};
 
 pub struct App {
     state: crate::state::AppState,
@@ -16,28 +16,146 @@ impl App {
     }
 
     pub async fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
-        // Load initial data
-        self.state.data_manager.load_pools().await?;
+        // Load initial data in the background so the first frame renders immediately
+        self.state.data_manager.start_loading_pools();
+        if self.state.config.check_updates {
+            self.state.update_check = Some(tokio::spawn(crate::update::newer_version()));
+        }
 
         loop {
+            match self.state.data_manager.poll_pool_load().await {
+                Ok(true) => {
+                    self.state.sort_manager.sort_pools(&mut self.state.data_manager.pools);
+                    if let Some(pool_name) = self.state.config.pool.clone() {
+                        Navigator::open_pool(&mut self.state, &pool_name).await?;
+                    }
+                }
+                Ok(false) => {}
+                Err(e) => self.state.set_error(format!("Failed to load pools: {:#}", e)),
+            }
+            if let Some(handle) = self.state.update_check.take_if(|handle| handle.is_finished()) {
+                self.state.available_update = handle.await.ok().flatten();
+            }
+            self.poll_iostat().await;
+            for message in self.state.data_manager.take_prefetch_errors() {
+                self.state.record_error(message);
+            }
+            self.state.tick = self.state.tick.wrapping_add(1);
+
             // Check for timeout expiration
             if self.state.delete_confirmation_pending && self.state.is_delete_confirmation_expired() {
                 self.state.clear_delete_confirmation();
             }
+            if self.state.quit_confirmation_timestamp.is_some() && self.state.is_quit_confirmation_expired() {
+                self.state.quit_confirmation_timestamp = None;
+                self.state.info_message = None;
+            }
 
             // Draw UI first to ensure error messages are visible
             terminal.draw(|f| crate::ui::draw(f, &mut self.state))?;
 
             // Use timeout to allow periodic UI updates during background operations
-            if event::poll(std::time::Duration::from_millis(100))?
-                && let Event::Key(key) = event::read()? {
-                    Navigator::handle_key_event(&mut self.state, key.code, key.modifiers).await?;
+            if event::poll(std::time::Duration::from_millis(100))? {
+                match event::read()? {
+                    Event::Key(key) => {
+                        Navigator::handle_key_event(&mut self.state, key.code, key.modifiers).await?;
+                    }
+                    Event::Mouse(mouse) => Navigator::handle_mouse_event(&mut self.state, mouse),
+                    // Draw at the new size right away; drawing resizes the buffers and
+                    // recomputes the scroll offsets and column widths from the new area
+                    Event::Resize(_, _) => {
+                        terminal.draw(|f| crate::ui::draw(f, &mut self.state))?;
+                    }
+                    _ => {}
                 }
+            }
+
+            if let Some(command) = self.state.pending_shell_command.take() {
+                match crate::terminal::with_suspended_terminal(terminal, || crate::terminal::run_shell_command(&command)) {
+                    Ok(Ok(status)) if !status.success() => self.state.set_info(format!("{} exited with {}", command, status)),
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) | Err(e) => self.state.set_error(format!("Failed to run {}: {:#}", command, e)),
+                }
+            }
 
             if self.state.should_quit {
                 break;
             }
         }
+
+        // Don't leave a prefetch running zfs commands while the process shuts down
+        self.state.data_manager.cancel_prefetch();
+        if let Some(handle) = self.state.update_check.take() {
+            handle.abort();
+        }
+        if let Some((_, handle)) = self.state.iostat_task.take() {
+            handle.abort();
+        }
         Ok(())
     }
+
+    /// Keep the live I/O modal fed with one sample at a time while it is open. Each sample
+    /// takes a second, which sets the refresh rate; closing the modal stops sampling.
+    async fn poll_iostat(&mut self) {
+        let state = &mut self.state;
+        let pool_name = state.modal.as_ref().and_then(|modal| modal.iostat_pool.clone());
+        // A sample taken for a modal that has since closed or changed pools is dropped
+        if let Some((_, handle)) = state.iostat_task.take_if(|(task_pool, _)| pool_name.as_ref() != Some(task_pool)) {
+            handle.abort();
+        }
+        let Some(pool_name) = pool_name else {
+            return;
+        };
+
+        if let Some((_, handle)) = state.iostat_task.take_if(|(_, handle)| handle.is_finished()) {
+            let units = state.config.units;
+            let result = handle.await;
+            let Some(modal) = state.modal.as_mut() else {
+                return;
+            };
+            match result {
+                Ok(Ok(iostat)) => {
+                    modal.lines = vec![
+                        format!("Read:  {:>8} ops/s  {:>10}/s", iostat.read_ops, format_bytes(iostat.read_bytes, units)),
+                        format!("Write: {:>8} ops/s  {:>10}/s", iostat.write_ops, format_bytes(iostat.write_bytes, units)),
+                        String::new(),
+                        "Sampled every second; Esc to close".to_string(),
+                    ];
+                }
+                // Don't retry a failing command in a tight loop
+                Ok(Err(e)) => {
+                    modal.lines = vec![format!("{:#}", e)];
+                    modal.iostat_pool = None;
+                    return;
+                }
+                Err(_) => return,
+            }
+        }
+
+        if state.iostat_task.is_none() {
+            let runner = state.data_manager.runner;
+            let task_pool = pool_name.clone();
+            let handle = tokio::spawn(async move { crate::zfs::get_pool_iostat(&runner, &task_pool).await });
+            state.iostat_task = Some((pool_name, handle));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Modal;
+
+    #[tokio::test]
+    async fn iostat_samples_never_land_in_another_modal() {
+        let mut app = App::new(Config::default());
+        let handle = tokio::spawn(async { Ok(crate::zfs::PoolIostat { read_ops: 7, ..Default::default() }) });
+        app.state.iostat_task = Some(("tank".to_string(), handle));
+        app.state.modal = Some(Modal::new("Properties: tank/home".to_string(), vec!["used".to_string()]));
+
+        app.poll_iostat().await;
+
+        assert!(app.state.iostat_task.is_none());
+        assert_eq!(app.state.modal.unwrap().lines, ["used"]);
+    }
 }
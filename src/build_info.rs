@@ -0,0 +1,26 @@
+// Constants generated by `built` in build.rs; most of them go unused
+#[allow(dead_code)]
+mod built_info {
+    include!(concat!(env!("OUT_DIR"), "/built.rs"));
+}
+
+/// Short commit hash with a `-dirty` suffix for builds from a modified tree, if known
+pub fn commit() -> String {
+    match (built_info::GIT_COMMIT_HASH_SHORT, built_info::GIT_DIRTY) {
+        (Some(hash), Some(true)) => format!("{}-dirty", hash),
+        (Some(hash), _) => hash.to_string(),
+        (None, _) => "unknown".to_string(),
+    }
+}
+
+/// Version, commit, build time, profile, target and compiler, one per line, for bug reports
+pub fn describe() -> Vec<String> {
+    vec![
+        format!("{} {}", built_info::PKG_NAME, built_info::PKG_VERSION),
+        format!("commit:  {}", commit()),
+        format!("built:   {}", built_info::BUILT_TIME_UTC),
+        format!("profile: {}", built_info::PROFILE),
+        format!("target:  {}", built_info::TARGET),
+        format!("rustc:   {}", built_info::RUSTC_VERSION),
+    ]
+}
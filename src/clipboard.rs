@@ -0,0 +1,40 @@
+use anyhow::{anyhow, Context, Result};
+use std::process::Stdio;
+use tokio::{io::AsyncWriteExt, process::Command};
+
+/// Clipboard helpers tried in order; the first one that is installed and succeeds wins
+const CLIPBOARD_COMMANDS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+pub async fn copy_to_clipboard(text: &str) -> Result<()> {
+    for (command, args) in CLIPBOARD_COMMANDS {
+        if pipe_to_command(command, args, text).await.is_ok() {
+            return Ok(());
+        }
+    }
+    Err(anyhow!("No clipboard available"))
+}
+
+async fn pipe_to_command(command: &str, args: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to execute command: {}", command))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).await?;
+    }
+
+    let status = child.wait().await?;
+    if !status.success() {
+        return Err(anyhow!("Command failed: {}", command));
+    }
+    Ok(())
+}
@@ -1,4 +1,7 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::sorting::{DatasetSortOrder, SnapshotSortOrder};
 
 #[derive(Parser, Debug, Clone)]
 #[command(name = "zfs_space_visualizer")]
@@ -9,19 +12,171 @@ pub struct Config {
     #[command(subcommand)]
     pub command: Option<Commands>,
 
+    /// Print version and build details (commit, build time, profile, target) and exit
+    #[arg(long, help = "Print version, commit, build time, profile, target and rustc version, then exit")]
+    pub version_verbose: bool,
+
     /// Enable readonly mode (disables delete functionality)
     #[arg(long, help = "Enable readonly mode to disable delete functionality")]
     pub readonly: bool,
 
+    /// Log destructive commands and report what they would do instead of running them
+    #[arg(long, help = "Rehearse deletes and destroys: log the zfs command and report it without running it")]
+    pub dry_run: bool,
+
     /// Number of threads to use for dataset refresh operations
     #[arg(long, value_name = "NUM", help = "Number of threads for dataset operations (default: auto-detected)")]
     pub threads: Option<usize>,
+
+    /// Leave mouse reporting off so the terminal's own text selection keeps working
+    #[arg(long, help = "Disable mouse capture so terminal text selection and copy/paste work")]
+    pub no_mouse: bool,
+
+    /// Seconds before a zfs/zpool command is killed and reported as an error
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_COMMAND_TIMEOUT_SECS, help = "Seconds before a zfs/zpool command is abandoned, e.g. on a hung pool (0 = no limit)")]
+    pub command_timeout: u64,
+
+    /// Skip the startup prefetch and only list snapshots when a dataset is opened
+    #[arg(long, help = "Disable background snapshot prefetch; snapshots are listed only when a dataset is opened")]
+    pub no_prefetch: bool,
+
+    /// Maximum number of datasets whose snapshot lists are kept in memory
+    #[arg(long, value_name = "DATASETS", default_value_t = DEFAULT_CACHE_SIZE, help = "Number of datasets whose snapshots are cached; least recently viewed are evicted")]
+    pub cache_size: usize,
+
+    /// Path to a TOML palette file to add as a custom theme
+    #[arg(long, value_name = "PATH", help = "Load an additional custom theme from a TOML palette file")]
+    pub theme_file: Option<PathBuf>,
+
+    /// Look up the latest release in the background and mention it in the status bar
+    #[arg(long, help = "Check GitHub for a newer release on startup and show a note in the status bar")]
+    pub check_updates: bool,
+
+    /// Path to a TOML file remapping the navigation keys
+    #[arg(long, value_name = "PATH", help = "Remap navigation, delete, sort, help and quit keys from a TOML keymap file")]
+    pub keymap_file: Option<PathBuf>,
+
+    /// Units used when displaying sizes
+    #[arg(long, value_enum, default_value_t = ByteUnits::Binary, help = "Size units: binary (KiB, 1024-based), si (KB, 1000-based) or raw (exact bytes)")]
+    pub units: ByteUnits,
+
+    /// Seconds before a pending delete confirmation expires
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_CONFIRM_TIMEOUT_SECS, help = "Seconds to wait for the second 'd' press before cancelling a delete (0 = never expire)")]
+    pub confirm_timeout: u64,
+
+    /// Require a second 'q' within the confirmation timeout before quitting
+    #[arg(long, help = "Ask for a second 'q' before quitting (Ctrl+C still quits immediately)")]
+    pub confirm_quit: bool,
+
+    /// Highlight datasets whose usage exceeds this percentage of used + available
+    #[arg(long, value_name = "PCT", default_value_t = 90, help = "Percent full at which datasets are highlighted as near their quota or pool limit")]
+    pub warn_threshold: u8,
+
+    /// Hide datasets whose full name matches; repeat for several patterns
+    #[arg(long, value_name = "REGEX", help = "Hide datasets whose name matches this regex, e.g. '/iocage' or '\\.system' (repeatable)")]
+    pub hide_pattern: Vec<String>,
+
+    /// Hide datasets and snapshots that use less than this many bytes
+    #[arg(long, value_name = "SIZE", value_parser = crate::zfs::parse_size, default_value = "0", help = "Hide datasets and snapshots using less than this, in bytes or with a suffix like 500M or 2G")]
+    pub min_size: u64,
+
+    /// Glyphs used to draw the usage bars
+    #[arg(long, value_enum, default_value_t = BarStyle::Block, help = "Bar glyphs: block (█ with eighth-block ends), shade (▓), hash or ascii (#), or equals (=)")]
+    pub bar_style: BarStyle,
+
+    /// Snapshots older than this many days are highlighted as pruning candidates
+    #[arg(long, value_name = "DAYS", default_value_t = 90, help = "Highlight snapshots older than this many days (0 = never)")]
+    pub stale_days: u64,
+
+    /// Initial sort order of the dataset view
+    #[arg(long, value_enum, value_name = "ORDER", default_value_t = DatasetSortOrder::TotalSizeDesc, help = "Initial dataset sort order; s still cycles through the others")]
+    pub sort_datasets: DatasetSortOrder,
+
+    /// Initial sort order of the snapshot view
+    #[arg(long, value_enum, value_name = "ORDER", default_value_t = SnapshotSortOrder::UsedDesc, help = "Initial snapshot sort order; s still cycles through the others")]
+    pub sort_snapshots: SnapshotSortOrder,
+
+    /// Rows moved by PageUp/PageDown; defaults to the visible list height
+    #[arg(long, value_name = "ROWS", help = "Rows to move with PageUp/PageDown (defaults to a full screen)")]
+    pub page_size: Option<usize>,
+
+    /// Start directly in the dataset view of this pool
+    #[arg(long, value_name = "NAME", help = "Open this pool's dataset view on startup instead of the pool list")]
+    pub pool: Option<String>,
+
+    /// Append executed commands, their exit status and errors to this file
+    #[arg(long, value_name = "PATH", help = "Log executed zfs/zpool commands and errors to a file for debugging")]
+    pub log_file: Option<PathBuf>,
+
+    /// Read pools, datasets and snapshots from a JSON fixture instead of running ZFS commands
+    #[arg(long, value_name = "FILE", hide = true)]
+    pub fixture: Option<PathBuf>,
+}
+
+pub const DEFAULT_CONFIRM_TIMEOUT_SECS: u64 = 3;
+pub const DEFAULT_CACHE_SIZE: usize = 1000;
+pub const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 30;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ByteUnits {
+    /// 1024-based units labelled KiB, MiB, GiB, ...
+    #[default]
+    Binary,
+    /// 1000-based units labelled KB, MB, GB, ...
+    Si,
+    /// Exact byte counts
+    Raw,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum BarStyle {
+    /// Full blocks, with the last cell filled in eighths for finer resolution: █▌
+    #[default]
+    Block,
+    /// Dark shade blocks, softer on some fonts: ▓
+    Shade,
+    /// Plain ASCII: #
+    #[value(alias = "ascii")]
+    Hash,
+    /// Plain ASCII: =
+    Equals,
+}
+
+impl BarStyle {
+    pub const fn fill(self) -> char {
+        match self {
+            BarStyle::Block => '█',
+            BarStyle::Shade => '▓',
+            BarStyle::Hash => '#',
+            BarStyle::Equals => '=',
+        }
+    }
+
+    /// Glyph for a cell that is only `fraction` filled, if the style draws partial cells
+    pub fn partial(self, fraction: f64) -> Option<char> {
+        const EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+        if self != BarStyle::Block {
+            return None;
+        }
+        let eighths = (fraction * 8.0) as usize;
+        (1..8).contains(&eighths).then(|| EIGHTHS[eighths - 1])
+    }
 }
 
 #[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
     /// Update the application to the latest version
-    Update,
+    Update {
+        /// Restore the binary that the last update replaced
+        #[arg(long)]
+        rollback: bool,
+    },
+    /// Print pool usage without starting the TUI
+    Report {
+        /// Exit with status 2 when any pool's capacity reaches this percentage
+        #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(1..=100))]
+        alarm_threshold: Option<u8>,
+    },
 }
 
 
@@ -30,6 +185,11 @@ impl Config {
         Config::parse()
     }
 
+    /// The --hide-pattern regexes; `validate` has already rejected any that don't compile
+    pub fn hide_patterns(&self) -> Vec<regex::Regex> {
+        self.hide_pattern.iter().filter_map(|pattern| regex::Regex::new(pattern).ok()).collect()
+    }
+
     /// Get the effective thread count, using auto-detection if not specified
     pub fn effective_thread_count(&self) -> usize {
         self.threads.unwrap_or_else(|| {
@@ -50,6 +210,20 @@ impl Config {
                 return Err("Thread count must not exceed 1000".to_string());
             }
         }
+        if self.cache_size == 0 {
+            return Err("Cache size must be at least 1".to_string());
+        }
+        if self.page_size == Some(0) {
+            return Err("Page size must be at least 1".to_string());
+        }
+        for pattern in &self.hide_pattern {
+            if let Err(e) = regex::Regex::new(pattern) {
+                return Err(format!("Invalid --hide-pattern '{}': {}", pattern, e));
+            }
+        }
+        if self.warn_threshold == 0 || self.warn_threshold > 100 {
+            return Err("Warning threshold must be between 1 and 100".to_string());
+        }
         Ok(())
     }
 }
@@ -1,71 +1,211 @@
 use anyhow::Result;
-use futures::future;
+use lru::LruCache;
+use regex::Regex;
 use std::{
-    collections::HashMap,
+    collections::HashSet,
+    num::NonZeroUsize,
     sync::{Arc, Mutex, atomic::{AtomicBool, AtomicUsize, Ordering}},
 };
-use tokio::task;
+use tokio::task::{self, JoinHandle, JoinSet};
 
-use crate::zfs::{Pool, Dataset, Snapshot};
+use crate::{
+    sorting::SortManager,
+    zfs::{Pool, Dataset, Snapshot, Listing, TokioCommandRunner},
+};
+
+/// Largest values in the dataset list, used to scale the bars relative to each other
+pub struct DatasetScalingValues {
+    pub max_dataset_size: u64,
+    pub max_snapshot_size: u64,
+    pub max_total_size: u64,
+    pub max_used_size: u64,
+}
+
+impl DatasetScalingValues {
+    fn from_datasets(datasets: &[Dataset]) -> Self {
+        let mut scaling = Self {
+            max_dataset_size: 1,
+            max_snapshot_size: 1,
+            max_total_size: 1,
+            max_used_size: 1,
+        };
+        for d in datasets {
+            let snapshot_bytes = d.snapshot_bytes();
+            scaling.max_dataset_size = scaling.max_dataset_size.max(d.referenced);
+            scaling.max_snapshot_size = scaling.max_snapshot_size.max(snapshot_bytes);
+            scaling.max_total_size = scaling.max_total_size.max(d.referenced + snapshot_bytes);
+            scaling.max_used_size = scaling
+                .max_used_size
+                .max(d.used_by_dataset + d.used_by_children + d.used_by_refreservation + snapshot_bytes);
+        }
+        scaling
+    }
+
+    /// Scale every bar against the same total, e.g. the pool's usable size
+    pub fn relative_to(total: u64) -> Self {
+        let total = total.max(1);
+        Self {
+            max_dataset_size: total,
+            max_snapshot_size: total,
+            max_total_size: total,
+            max_used_size: total,
+        }
+    }
+}
+
+/// Largest values in the snapshot list, used to scale the bars relative to each other
+pub struct SnapshotScalingValues {
+    pub max_used_size: u64,
+    pub max_referenced_size: u64,
+    pub max_written_size: u64,
+}
+
+impl SnapshotScalingValues {
+    fn from_snapshots(snapshots: &[Snapshot]) -> Self {
+        Self {
+            max_used_size: snapshots.iter().map(|s| s.used).max().unwrap_or(1),
+            max_referenced_size: snapshots.iter().map(|s| s.referenced).max().unwrap_or(1),
+            max_written_size: snapshots.iter().map(|s| s.written).max().unwrap_or(1),
+        }
+    }
+}
 
 pub struct DataManager {
+    // Carries the command timeout to every `zfs`/`zpool` call, including the background ones
+    pub runner: TokioCommandRunner,
     pub pools: Vec<Pool>,
+    // Rows of the dataset view; in tree view these are nested and skip collapsed subtrees
     pub datasets: Vec<Dataset>,
+    // Every dataset in the open pool, as listed by ZFS
+    pub pool_datasets: Vec<Dataset>,
     pub snapshots: Vec<Snapshot>,
-    pub snapshot_cache: Arc<Mutex<HashMap<String, Vec<Snapshot>>>>,
+    // Bounded so sessions on systems with thousands of datasets don't grow without limit
+    pub snapshot_cache: Arc<Mutex<LruCache<String, Vec<Snapshot>>>>,
     pub prefetch_complete: Arc<AtomicBool>,
     pub prefetch_total: Arc<AtomicUsize>,
     pub prefetch_completed: Arc<AtomicUsize>,
+    // Failures the prefetch ran into, drained into the error log by the UI loop
+    pub prefetch_errors: Arc<Mutex<Vec<String>>>,
+    // Pools and datasets the current prefetch failed to list, kept after the messages are drained
+    pub prefetch_failures: Arc<AtomicUsize>,
     pub thread_count: usize,
+    pub prefetch_enabled: bool,
+    pub pool_load: Option<JoinHandle<Result<Listing<Pool>>>>,
+    // Kept so the prefetch can be aborted on quit or before starting another one
+    pub prefetch: Option<JoinHandle<()>>,
+    // Computed when the lists are loaded rather than on every frame; sorting doesn't change them
+    pub dataset_scaling: DatasetScalingValues,
+    pub snapshot_scaling: SnapshotScalingValues,
+    // Datasets matching any of these are left out of the dataset rows
+    pub hide_patterns: Vec<Regex>,
+    // Datasets and snapshots using fewer bytes than this are left out of the views
+    pub min_size: u64,
+    // Unparseable rows in the latest pool, dataset and snapshot listings; each load replaces its own
+    pub skipped_pool_rows: usize,
+    pub skipped_dataset_rows: usize,
+    pub skipped_snapshot_rows: usize,
 }
 
 impl DataManager {
-    pub fn new(thread_count: usize) -> Self {
+    pub fn new(runner: TokioCommandRunner, thread_count: usize, cache_size: usize, prefetch_enabled: bool, hide_patterns: Vec<Regex>, min_size: u64) -> Self {
+        let cache_size = NonZeroUsize::new(cache_size).unwrap_or(NonZeroUsize::MIN);
         Self {
+            runner,
             pools: Vec::new(),
             datasets: Vec::new(),
+            pool_datasets: Vec::new(),
             snapshots: Vec::new(),
-            snapshot_cache: Arc::new(Mutex::new(HashMap::new())),
-            prefetch_complete: Arc::new(AtomicBool::new(false)),
+            snapshot_cache: Arc::new(Mutex::new(LruCache::new(cache_size))),
+            // With prefetch disabled there is nothing to wait for, so report it as done
+            prefetch_complete: Arc::new(AtomicBool::new(!prefetch_enabled)),
             prefetch_total: Arc::new(AtomicUsize::new(0)),
             prefetch_completed: Arc::new(AtomicUsize::new(0)),
+            prefetch_errors: Arc::new(Mutex::new(Vec::new())),
+            prefetch_failures: Arc::new(AtomicUsize::new(0)),
             thread_count,
+            prefetch_enabled,
+            pool_load: None,
+            prefetch: None,
+            dataset_scaling: DatasetScalingValues::from_datasets(&[]),
+            snapshot_scaling: SnapshotScalingValues::from_snapshots(&[]),
+            hide_patterns,
+            min_size,
+            skipped_pool_rows: 0,
+            skipped_dataset_rows: 0,
+            skipped_snapshot_rows: 0,
         }
     }
 
-    pub async fn load_pools(&mut self) -> Result<()> {
-        self.pools = crate::zfs::get_pools().await?;
+    /// Rows missing from the current listings because ZFS printed something that couldn't be parsed
+    pub fn skipped_row_count(&self) -> usize {
+        self.skipped_pool_rows + self.skipped_dataset_rows + self.skipped_snapshot_rows
+    }
+
+    /// Start listing pools in the background so the UI can render while it runs
+    pub fn start_loading_pools(&mut self) {
+        let runner = self.runner;
+        self.pool_load = Some(task::spawn(async move { crate::zfs::get_pools(&runner).await }));
+    }
+
+    pub fn is_loading_pools(&self) -> bool {
+        self.pool_load.is_some()
+    }
+
+    /// Pick up the result of the background pool load once it has finished.
+    /// Returns true when pools were loaded by this call.
+    pub async fn poll_pool_load(&mut self) -> Result<bool> {
+        if !self.pool_load.as_ref().is_some_and(|handle| handle.is_finished()) {
+            return Ok(false);
+        }
+        let Some(handle) = self.pool_load.take() else {
+            return Ok(false);
+        };
+        let listing = handle.await??;
+        self.pools = listing.rows;
+        self.skipped_pool_rows = listing.skipped.len();
 
         // Start background prefetch of all snapshots (non-blocking)
-        self.start_background_prefetch();
+        if self.prefetch_enabled {
+            self.start_background_prefetch();
+        }
 
-        Ok(())
+        Ok(true)
     }
 
     fn start_background_prefetch(&mut self) {
+        // Never let two prefetches race on the cache
+        self.cancel_prefetch();
+        self.prefetch_complete.store(false, Ordering::Relaxed);
+        self.prefetch_failures.store(0, Ordering::Relaxed);
+
         let pools = self.pools.clone();
         let cache = Arc::clone(&self.snapshot_cache);
         let prefetch_complete = Arc::clone(&self.prefetch_complete);
         let prefetch_total = Arc::clone(&self.prefetch_total);
         let prefetch_completed = Arc::clone(&self.prefetch_completed);
+        let prefetch_errors = Arc::clone(&self.prefetch_errors);
+        let prefetch_failures = Arc::clone(&self.prefetch_failures);
         let thread_count = self.thread_count;
+        let runner = self.runner;
 
-        task::spawn(async move {
+        self.prefetch = Some(task::spawn(async move {
             // Get all datasets from all pools
             let mut all_datasets = Vec::new();
 
             for pool in &pools {
-                match crate::zfs::get_datasets(&pool.name).await {
+                match crate::zfs::get_datasets(&runner, &pool.name).await {
                     Ok(datasets) => {
-                        all_datasets.extend(datasets);
-                    }
-                    Err(_) => {
-                        // Continue with other pools if one fails
-                        continue;
+                        all_datasets.extend(datasets.rows);
                     }
+                    // Continue with other pools if one fails
+                    Err(e) => record_prefetch_error(&prefetch_errors, &prefetch_failures, format!("Failed to list datasets in {}: {:#}", pool.name, e)),
                 }
             }
 
+            // Prefetching more datasets than the cache holds would only evict earlier results
+            let capacity = cache.lock().map(|cache_lock| cache_lock.cap().get()).unwrap_or(usize::MAX);
+            all_datasets.truncate(capacity);
+
             // Set total count for progress tracking
             prefetch_total.store(all_datasets.len(), Ordering::Relaxed);
             prefetch_completed.store(0, Ordering::Relaxed);
@@ -75,70 +215,135 @@ impl DataManager {
             let max_concurrent = thread_count;
             let semaphore = Arc::new(tokio::sync::Semaphore::new(max_concurrent));
 
-            // Prefetch snapshots for each dataset in parallel
-            let tasks: Vec<_> = all_datasets
-                .into_iter()
-                .map(|dataset| {
-                    let cache = Arc::clone(&cache);
-                    let sem = Arc::clone(&semaphore);
-                    let completed = Arc::clone(&prefetch_completed);
-
-                    task::spawn(async move {
-                        // Acquire semaphore permit to limit concurrency
-                        let _permit = sem.acquire().await.ok()?;
-
-                        let result = match crate::zfs::get_snapshots(&dataset.name).await {
-                            Ok(snapshots) => {
-                                if let Ok(mut cache_lock) = cache.lock() {
-                                    cache_lock.insert(dataset.name.clone(), snapshots);
-                                }
-                                Some(())
+            // Prefetch snapshots for each dataset in parallel. The tasks live in a JoinSet so
+            // aborting this task drops the set and aborts them too.
+            let mut tasks = JoinSet::new();
+            for dataset in all_datasets {
+                let cache = Arc::clone(&cache);
+                let sem = Arc::clone(&semaphore);
+                let completed = Arc::clone(&prefetch_completed);
+                let errors = Arc::clone(&prefetch_errors);
+                let failures = Arc::clone(&prefetch_failures);
+
+                tasks.spawn(async move {
+                    // Acquire semaphore permit to limit concurrency
+                    let Ok(_permit) = sem.acquire().await else {
+                        return;
+                    };
+
+                    // Failures are only logged; the dataset is fetched on demand when opened
+                    match crate::zfs::get_snapshots(&runner, &dataset.name).await {
+                        Ok(snapshots) => {
+                            // The cache only holds rows, so say here that some are missing
+                            if !snapshots.skipped.is_empty() {
+                                record_prefetch_error(&errors, &failures, format!("Skipped {} unparseable snapshot rows of {}", snapshots.skipped.len(), dataset.name));
                             }
-                            Err(_) => {
-                                // Continue with other datasets if one fails
-                                None
+                            // Don't displace lists the user has already viewed
+                            if let Ok(mut cache_lock) = cache.lock()
+                                && !cache_lock.contains(&dataset.name)
+                            {
+                                cache_lock.put(dataset.name.clone(), snapshots.rows);
                             }
-                        };
+                        }
+                        Err(e) => record_prefetch_error(&errors, &failures, format!("Failed to prefetch snapshots of {}: {:#}", dataset.name, e)),
+                    }
 
-                        // Increment completed count
-                        completed.fetch_add(1, Ordering::Relaxed);
-                        result
-                    })
-                })
-                .collect();
+                    // Increment completed count
+                    completed.fetch_add(1, Ordering::Relaxed);
+                });
+            }
 
             // Wait for all snapshot fetches to complete
-            future::join_all(tasks).await;
+            while tasks.join_next().await.is_some() {}
 
             // Signal completion
             prefetch_complete.store(true, Ordering::Relaxed);
-        });
+        }));
+    }
+
+    pub fn prefetch_failure_count(&self) -> usize {
+        self.prefetch_failures.load(Ordering::Relaxed)
+    }
+
+    /// Failures recorded by the prefetch since the last call
+    pub fn take_prefetch_errors(&self) -> Vec<String> {
+        self.prefetch_errors.lock().map(|mut errors| std::mem::take(&mut *errors)).unwrap_or_default()
+    }
+
+    /// Abort a running prefetch along with any `zfs list` processes it has in flight
+    pub fn cancel_prefetch(&mut self) {
+        if let Some(handle) = self.prefetch.take() {
+            handle.abort();
+        }
     }
 
     pub async fn load_datasets(&mut self, pool_name: &str) -> Result<()> {
-        self.datasets = crate::zfs::get_datasets(pool_name).await?;
+        let listing = crate::zfs::get_datasets(&self.runner, pool_name).await?;
+        self.pool_datasets = listing.rows;
+        self.skipped_dataset_rows = listing.skipped.len();
+        self.datasets = self.pool_datasets.clone();
+        self.dataset_scaling = DatasetScalingValues::from_datasets(&self.datasets);
         Ok(())
     }
 
+    /// Rebuild the dataset rows from the pool's datasets in sort order, nested as a tree
+    /// with the `collapsed` subtrees folded into their parents when given. `hide_inactive`
+    /// leaves out datasets with canmount=off or nothing used; `--min-size` and
+    /// `--hide-pattern` always apply.
+    pub fn arrange_datasets(&mut self, sort_manager: &SortManager, collapsed: Option<&HashSet<String>>, hide_inactive: bool) {
+        sort_manager.sort_datasets(&mut self.pool_datasets);
+        let visible: Vec<Dataset> = self
+            .pool_datasets
+            .iter()
+            .filter(|d| !hide_inactive || !(d.mount_disabled || d.used == 0))
+            .filter(|d| d.used >= self.min_size && !self.hide_patterns.iter().any(|pattern| pattern.is_match(&d.name)))
+            .cloned()
+            .collect();
+        self.datasets = match collapsed {
+            Some(collapsed) => crate::dataset_tree::arrange(&visible, collapsed),
+            None => visible,
+        };
+        self.dataset_scaling = DatasetScalingValues::from_datasets(&self.datasets);
+    }
+
     pub async fn load_snapshots(&mut self, dataset_name: &str) -> Result<()> {
         self.snapshots = self.get_cached_snapshots(dataset_name).unwrap_or_default();
+        self.skipped_snapshot_rows = 0;
 
         if self.snapshots.is_empty() {
-            self.snapshots = crate::zfs::get_snapshots(dataset_name).await?;
-            self.cache_snapshots(dataset_name);
+            self.fetch_snapshots(dataset_name).await?;
         }
+        self.drop_small_snapshots();
+        self.snapshot_scaling = SnapshotScalingValues::from_snapshots(&self.snapshots);
 
         Ok(())
     }
 
     pub async fn reload_snapshots(&mut self, dataset_name: &str) -> Result<()> {
-        // Force reload from ZFS, bypassing cache
-        self.snapshots = crate::zfs::get_snapshots(dataset_name).await?;
+        // Force reload from ZFS, bypassing cache; related entries went stale with this one
+        self.invalidate_snapshots(dataset_name);
+        self.fetch_snapshots(dataset_name).await?;
+        self.drop_small_snapshots();
+        self.snapshot_scaling = SnapshotScalingValues::from_snapshots(&self.snapshots);
+        Ok(())
+    }
+
+    async fn fetch_snapshots(&mut self, dataset_name: &str) -> Result<()> {
+        let listing = crate::zfs::get_snapshots(&self.runner, dataset_name).await?;
+        self.snapshots = listing.rows;
+        self.skipped_snapshot_rows = listing.skipped.len();
         self.cache_snapshots(dataset_name);
         Ok(())
     }
 
+    /// Apply --min-size to the snapshot list; the cache keeps the full listing
+    fn drop_small_snapshots(&mut self) {
+        let min_size = self.min_size;
+        self.snapshots.retain(|s| s.used >= min_size);
+    }
+
     pub fn get_cached_snapshots(&self, dataset_name: &str) -> Option<Vec<Snapshot>> {
+        // Looking an entry up marks it as recently used
         self.snapshot_cache
             .lock()
             .ok()?
@@ -146,9 +351,53 @@ impl DataManager {
             .cloned()
     }
 
+    /// Fill the snapshot list with every cached snapshot. Cached listings are recursive, so a
+    /// child's snapshots also sit under each ancestor's entry; each is kept only once.
+    pub fn load_all_cached_snapshots(&mut self) {
+        let mut seen = HashSet::new();
+        self.snapshots = match self.snapshot_cache.lock() {
+            Ok(cache_lock) => cache_lock
+                .iter()
+                .flat_map(|(_, snapshots)| snapshots)
+                .filter(|snapshot| seen.insert(snapshot.name.clone()))
+                .cloned()
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        self.skipped_snapshot_rows = 0;
+        self.drop_small_snapshots();
+        self.snapshot_scaling = SnapshotScalingValues::from_snapshots(&self.snapshots);
+    }
+
+    /// Number of cached snapshots for the dataset; unlike a lookup this leaves the LRU order alone
+    pub fn cached_snapshot_count(&self, dataset_name: &str) -> Option<usize> {
+        self.snapshot_cache
+            .lock()
+            .ok()?
+            .peek(dataset_name)
+            .map(Vec::len)
+    }
+
+    /// Drop every cached listing that includes this dataset's snapshots: its own, its
+    /// ancestors' (listings are recursive) and its descendants', so the next visit re-fetches
+    pub fn invalidate_snapshots(&self, dataset_name: &str) {
+        let Ok(mut cache_lock) = self.snapshot_cache.lock() else {
+            return;
+        };
+        let is_related = |cached: &str| {
+            cached == dataset_name
+                || dataset_name.strip_prefix(cached).is_some_and(|rest| rest.starts_with('/'))
+                || cached.strip_prefix(dataset_name).is_some_and(|rest| rest.starts_with('/'))
+        };
+        let stale: Vec<String> = cache_lock.iter().map(|(name, _)| name).filter(|name| is_related(name)).cloned().collect();
+        for name in stale {
+            cache_lock.pop(&name);
+        }
+    }
+
     pub fn cache_snapshots(&self, dataset_name: &str) {
         if let Ok(mut cache_lock) = self.snapshot_cache.lock() {
-            cache_lock.insert(dataset_name.to_string(), self.snapshots.clone());
+            cache_lock.put(dataset_name.to_string(), self.snapshots.clone());
         }
     }
 
@@ -161,4 +410,34 @@ impl DataManager {
         let completed = self.prefetch_completed.load(Ordering::Relaxed);
         (completed, total)
     }
-}
\ No newline at end of file
+}
+
+fn record_prefetch_error(errors: &Mutex<Vec<String>>, failures: &AtomicUsize, message: String) {
+    log::warn!("{}", message);
+    failures.fetch_add(1, Ordering::Relaxed);
+    if let Ok(mut errors) = errors.lock() {
+        errors.push(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset(name: &str, used: u64) -> Dataset {
+        Dataset { name: name.to_string(), used, ..Default::default() }
+    }
+
+    #[test]
+    fn hidden_datasets_stay_in_the_pool_listing() {
+        let hide = vec![Regex::new("scratch").unwrap()];
+        let mut data = DataManager::new(TokioCommandRunner::default(), 1, 1, false, hide, 100);
+        data.pool_datasets = vec![dataset("tank", 1000), dataset("tank/scratch", 500), dataset("tank/tiny", 10)];
+
+        data.arrange_datasets(&SortManager::default(), None, false);
+
+        let rows: Vec<&str> = data.datasets.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(rows, ["tank"]);
+        assert_eq!(data.pool_datasets.len(), 3);
+    }
+}
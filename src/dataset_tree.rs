@@ -0,0 +1,185 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::zfs::Dataset;
+
+/// Nesting level of a dataset below its pool's root dataset
+pub fn depth(name: &str) -> usize {
+    name.matches('/').count()
+}
+
+fn parent_name(name: &str) -> Option<&str> {
+    name.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+pub fn has_children(datasets: &[Dataset], name: &str) -> bool {
+    datasets.iter().any(|d| parent_name(&d.name) == Some(name))
+}
+
+/// Snapshot bytes of the named dataset and all of its descendants
+pub fn subtree_snapshot_bytes(datasets: &[Dataset], name: &str) -> u64 {
+    datasets
+        .iter()
+        .filter(|d| d.name == name || d.name.strip_prefix(name).is_some_and(|rest| rest.starts_with('/')))
+        .map(Dataset::snapshot_bytes)
+        .sum()
+}
+
+/// Arrange datasets so each one is followed by its descendants, keeping the existing order
+/// among siblings. Descendants of `collapsed` datasets are left out and their sizes added
+/// to the collapsed dataset's own, so its row shows the whole subtree.
+pub fn arrange(datasets: &[Dataset], collapsed: &HashSet<String>) -> Vec<Dataset> {
+    let names: HashSet<&str> = datasets.iter().map(|d| d.name.as_str()).collect();
+    let mut children: HashMap<&str, Vec<&Dataset>> = HashMap::new();
+    let mut roots = Vec::new();
+    for dataset in datasets {
+        match parent_name(&dataset.name).filter(|parent| names.contains(parent)) {
+            Some(parent) => children.entry(parent).or_default().push(dataset),
+            None => roots.push(dataset),
+        }
+    }
+
+    let mut rows = Vec::with_capacity(datasets.len());
+    // Walk depth first; pushing in reverse keeps siblings in their sorted order
+    let mut stack: Vec<&Dataset> = roots.into_iter().rev().collect();
+    while let Some(dataset) = stack.pop() {
+        if collapsed.contains(&dataset.name) {
+            rows.push(with_subtree_totals(dataset, &children));
+        } else {
+            rows.push(dataset.clone());
+            if let Some(kids) = children.get(dataset.name.as_str()) {
+                stack.extend(kids.iter().rev());
+            }
+        }
+    }
+    rows
+}
+
+/// Copy of `dataset` with the referenced, data, refreservation and snapshot bytes of all its
+/// descendants added in. `used` already covers the whole subtree, so the bytes moved into the
+/// other fields come out of `used_by_children` and the usage breakdown still adds up to it.
+fn with_subtree_totals(dataset: &Dataset, children: &HashMap<&str, Vec<&Dataset>>) -> Dataset {
+    let mut total = dataset.clone();
+    let mut pending = children.get(dataset.name.as_str()).cloned().unwrap_or_default();
+    while let Some(child) = pending.pop() {
+        let moved = child.used_by_dataset + child.used_by_refreservation + child.snapshot_bytes();
+        total.referenced += child.referenced;
+        total.used_by_dataset += child.used_by_dataset;
+        total.used_by_refreservation += child.used_by_refreservation;
+        total.snapshot_used = Some(total.snapshot_bytes() + child.snapshot_bytes());
+        total.used_by_children = total.used_by_children.saturating_sub(moved);
+        pending.extend(children.get(child.name.as_str()).into_iter().flatten());
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset(name: &str, referenced: u64, snapshot_used: u64) -> Dataset {
+        Dataset { name: name.to_string(), referenced, snapshot_used: Some(snapshot_used), ..Default::default() }
+    }
+
+    fn names(rows: &[Dataset]) -> Vec<&str> {
+        rows.iter().map(|d| d.name.as_str()).collect()
+    }
+
+    // Already in sort order, with children listed before their parents
+    fn sorted() -> Vec<Dataset> {
+        vec![
+            dataset("tank/home/bob", 50, 5),
+            dataset("tank/var", 40, 0),
+            dataset("tank/home", 10, 1),
+            dataset("tank/home/alice", 30, 3),
+            dataset("tank", 1, 0),
+        ]
+    }
+
+    #[test]
+    fn parents_come_before_their_children_in_sort_order() {
+        let rows = arrange(&sorted(), &HashSet::new());
+
+        assert_eq!(names(&rows), ["tank", "tank/var", "tank/home", "tank/home/bob", "tank/home/alice"]);
+    }
+
+    #[test]
+    fn collapsed_subtree_hides_descendants_and_sums_their_sizes() {
+        let collapsed = HashSet::from(["tank/home".to_string()]);
+
+        let rows = arrange(&sorted(), &collapsed);
+
+        assert_eq!(names(&rows), ["tank", "tank/var", "tank/home"]);
+        let home = &rows[2];
+        assert_eq!(home.referenced, 10 + 50 + 30);
+        assert_eq!(home.snapshot_used, Some(1 + 5 + 3));
+        // Rows outside the collapsed subtree keep their own sizes
+        assert_eq!(rows[1].referenced, 40);
+    }
+
+    #[test]
+    fn collapsed_sums_include_grandchildren() {
+        let datasets = vec![dataset("tank", 1, 1), dataset("tank/a", 2, 2), dataset("tank/a/b", 4, 4)];
+        let collapsed = HashSet::from(["tank".to_string()]);
+
+        let rows = arrange(&datasets, &collapsed);
+
+        assert_eq!(names(&rows), ["tank"]);
+        assert_eq!(rows[0].referenced, 7);
+        assert_eq!(rows[0].snapshot_used, Some(7));
+    }
+
+    /// A dataset as ZFS reports it, with `used` made up of its own parts plus its children's
+    fn with_usage(name: &str, own: u64, refreservation: u64, snapshots: u64, children: u64) -> Dataset {
+        Dataset {
+            name: name.to_string(),
+            used: own + refreservation + snapshots + children,
+            used_by_dataset: own,
+            used_by_refreservation: refreservation,
+            snapshot_used: Some(snapshots),
+            used_by_children: children,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn collapsed_breakdown_moves_descendant_usage_out_of_children() {
+        let datasets = vec![
+            with_usage("tank", 1, 0, 2, 10 + 20),
+            with_usage("tank/a", 3, 4, 3, 20),
+            with_usage("tank/a/b", 10, 5, 5, 0),
+        ];
+        let collapsed = HashSet::from(["tank".to_string()]);
+
+        let rows = arrange(&datasets, &collapsed);
+
+        let tank = &rows[0];
+        assert_eq!(tank.used, 33);
+        assert_eq!(tank.used_by_dataset, 1 + 3 + 10);
+        assert_eq!(tank.used_by_refreservation, 4 + 5);
+        assert_eq!(tank.snapshot_used, Some(2 + 3 + 5));
+        assert_eq!(tank.used_by_children, 0);
+        assert_eq!(tank.used_by_dataset + tank.used_by_refreservation + tank.snapshot_bytes() + tank.used_by_children, tank.used);
+    }
+
+    #[test]
+    fn collapsed_breakdown_keeps_children_that_are_not_listed() {
+        // A descendant left out of the listing, e.g. by --hide-pattern, stays in the children share
+        let datasets = vec![with_usage("tank", 1, 0, 0, 10 + 7), with_usage("tank/a", 6, 0, 4, 0)];
+        let collapsed = HashSet::from(["tank".to_string()]);
+
+        let rows = arrange(&datasets, &collapsed);
+
+        assert_eq!(rows[0].used_by_dataset, 7);
+        assert_eq!(rows[0].used_by_children, 7);
+        assert_eq!(rows[0].used, 18);
+    }
+
+    #[test]
+    fn datasets_whose_parent_is_hidden_become_roots() {
+        let datasets = vec![dataset("tank", 1, 0), dataset("tank/a/b", 4, 0)];
+
+        let rows = arrange(&datasets, &HashSet::new());
+
+        assert_eq!(names(&rows), ["tank", "tank/a/b"]);
+    }
+}
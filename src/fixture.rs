@@ -0,0 +1,125 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::{
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+
+use crate::zfs::{Dataset, Pool, Snapshot};
+
+/// Canned ZFS data used in place of `zpool`/`zfs` so the UI can run on machines without ZFS
+#[derive(Debug, Default, Deserialize)]
+pub struct Fixture {
+    #[serde(default)]
+    pub pools: Vec<Pool>,
+    #[serde(default)]
+    pub datasets: Vec<Dataset>,
+    #[serde(default)]
+    pub snapshots: Vec<Snapshot>,
+}
+
+static FIXTURE: OnceLock<Mutex<Fixture>> = OnceLock::new();
+
+/// Load a JSON fixture and route all ZFS queries to it for the rest of the process
+pub fn load(path: &Path) -> Result<()> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read fixture file {}", path.display()))?;
+    let fixture: Fixture = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse fixture file {}", path.display()))?;
+    FIXTURE
+        .set(Mutex::new(fixture))
+        .map_err(|_| anyhow!("A fixture has already been loaded"))
+}
+
+pub fn is_active() -> bool {
+    FIXTURE.get().is_some()
+}
+
+/// Run `f` against the loaded fixture, or return None when running against real ZFS
+pub fn with_fixture<T>(f: impl FnOnce(&mut Fixture) -> T) -> Option<T> {
+    let mut fixture = FIXTURE.get()?.lock().ok()?;
+    Some(f(&mut fixture))
+}
+
+impl Fixture {
+    /// Datasets in the pool, matching `zfs list -r <pool>`
+    pub fn datasets_in(&self, pool_name: &str) -> Vec<Dataset> {
+        self.datasets
+            .iter()
+            .filter(|d| is_same_or_descendant(&d.name, pool_name))
+            .cloned()
+            .collect()
+    }
+
+    /// Snapshots of the dataset and its descendants, matching `zfs list -t snap -r <dataset>`
+    pub fn snapshots_in(&self, dataset_name: &str) -> Vec<Snapshot> {
+        self.snapshots
+            .iter()
+            .filter(|s| {
+                s.name
+                    .split_once('@')
+                    .is_some_and(|(owner, _)| is_same_or_descendant(owner, dataset_name))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn create_dataset(&mut self, dataset_name: &str) -> Result<()> {
+        if self.datasets.iter().any(|d| d.name == dataset_name) {
+            return Err(anyhow!("cannot create '{}': dataset already exists", dataset_name));
+        }
+        let parent = dataset_name.rsplit_once('/').map_or("", |(parent, _)| parent);
+        if !self.datasets.iter().any(|d| d.name == parent) {
+            return Err(anyhow!("cannot create '{}': parent does not exist", dataset_name));
+        }
+        self.datasets.push(Dataset {
+            name: dataset_name.to_string(),
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    pub fn destroy_dataset(&mut self, dataset_name: &str, recursive: bool) -> Result<()> {
+        if !self.datasets.iter().any(|d| d.name == dataset_name) {
+            return Err(anyhow!("cannot destroy '{}': dataset does not exist", dataset_name));
+        }
+        let has_children = self.datasets.iter().any(|d| d.name != dataset_name && is_same_or_descendant(&d.name, dataset_name))
+            || !self.snapshots_in(dataset_name).is_empty();
+        if has_children && !recursive {
+            return Err(anyhow!("cannot destroy '{}': filesystem has children", dataset_name));
+        }
+        self.datasets.retain(|d| !is_same_or_descendant(&d.name, dataset_name));
+        self.snapshots.retain(|s| {
+            !s.name
+                .split_once('@')
+                .is_some_and(|(owner, _)| is_same_or_descendant(owner, dataset_name))
+        });
+        Ok(())
+    }
+
+    pub fn rename_snapshot(&mut self, old_name: &str, new_name: &str) -> Result<()> {
+        if self.snapshots.iter().any(|s| s.name == new_name) {
+            return Err(anyhow!("cannot rename to '{}': dataset already exists", new_name));
+        }
+        let snapshot = self
+            .snapshots
+            .iter_mut()
+            .find(|s| s.name == old_name)
+            .ok_or_else(|| anyhow!("cannot rename '{}': dataset does not exist", old_name))?;
+        snapshot.name = new_name.to_string();
+        Ok(())
+    }
+
+    pub fn delete_snapshot(&mut self, snapshot_name: &str) -> Result<()> {
+        let before = self.snapshots.len();
+        self.snapshots.retain(|s| s.name != snapshot_name);
+        if self.snapshots.len() == before {
+            return Err(anyhow!("cannot destroy '{}': dataset does not exist", snapshot_name));
+        }
+        Ok(())
+    }
+}
+
+fn is_same_or_descendant(name: &str, parent: &str) -> bool {
+    name == parent || name.strip_prefix(parent).is_some_and(|rest| rest.starts_with('/'))
+}
@@ -0,0 +1,289 @@
+use anyhow::{anyhow, Context, Result};
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Commands whose keys can be changed with a keymap file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    NavigateUp,
+    NavigateDown,
+    Delete,
+    Sort,
+    Help,
+    Quit,
+    Back,
+    Forward,
+}
+
+impl Action {
+    const ALL: [Action; 8] = [
+        Action::NavigateUp,
+        Action::NavigateDown,
+        Action::Delete,
+        Action::Sort,
+        Action::Help,
+        Action::Quit,
+        Action::Back,
+        Action::Forward,
+    ];
+
+    /// Name used for the action in keymap files
+    pub const fn name(self) -> &'static str {
+        match self {
+            Action::NavigateUp => "navigate_up",
+            Action::NavigateDown => "navigate_down",
+            Action::Delete => "delete",
+            Action::Sort => "sort",
+            Action::Help => "help",
+            Action::Quit => "quit",
+            Action::Back => "back",
+            Action::Forward => "forward",
+        }
+    }
+
+    const fn default_keys(self) -> &'static [KeyCode] {
+        match self {
+            Action::NavigateUp => &[KeyCode::Up, KeyCode::Char('k')],
+            Action::NavigateDown => &[KeyCode::Down, KeyCode::Char('j')],
+            Action::Delete => &[KeyCode::Char('d')],
+            Action::Sort => &[KeyCode::Char('s')],
+            Action::Help => &[KeyCode::Char('h')],
+            Action::Quit => &[KeyCode::Char('q')],
+            Action::Back => &[KeyCode::Left, KeyCode::Esc, KeyCode::Backspace],
+            Action::Forward => &[KeyCode::Right, KeyCode::Enter],
+        }
+    }
+}
+
+/// Keys with a fixed meaning in some view; binding an action to one would shadow it
+const FIXED_KEYS: &[KeyCode] = &[
+    KeyCode::Char('S'), KeyCode::Char('i'), KeyCode::Char('p'), KeyCode::Char('I'), KeyCode::Char('e'),
+    KeyCode::Char('V'), KeyCode::Char('f'), KeyCode::Char('y'), KeyCode::Char('b'), KeyCode::Char('u'),
+    KeyCode::Char('v'), KeyCode::Char(' '), KeyCode::Char('t'), KeyCode::Char('H'), KeyCode::Char('c'),
+    KeyCode::Char('X'), KeyCode::Char('r'), KeyCode::Char('P'), KeyCode::Char('!'), KeyCode::Char('a'),
+    KeyCode::Char('['), KeyCode::Char(']'), KeyCode::Char('g'), KeyCode::Char('G'), KeyCode::Char('/'),
+    KeyCode::PageUp, KeyCode::PageDown, KeyCode::Home, KeyCode::End,
+];
+
+/// How a key is written in the status bar and help screen
+fn key_label(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeyCode, Action>,
+    // Each action's keys in the order they were listed, for the on-screen hints
+    keys: HashMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_keys(|action| action.default_keys().to_vec()).expect("default keys do not overlap")
+    }
+}
+
+impl Keymap {
+    fn from_keys(mut keys_for: impl FnMut(Action) -> Vec<KeyCode>) -> Result<Self> {
+        let mut bindings = HashMap::new();
+        let mut keys = HashMap::new();
+        for action in Action::ALL {
+            let action_keys = keys_for(action);
+            for &key in &action_keys {
+                if FIXED_KEYS.contains(&key) {
+                    return Err(anyhow!("key `{}` for `{}` is already used by a built-in command", key, action.name()));
+                }
+                if let Some(other) = bindings.insert(key, action) {
+                    return Err(anyhow!(
+                        "key `{}` is bound to both `{}` and `{}`",
+                        key,
+                        other.name(),
+                        action.name()
+                    ));
+                }
+            }
+            keys.insert(action, action_keys);
+        }
+        Ok(Self { bindings, keys })
+    }
+
+    /// Up to `limit` of the action's keys for display, e.g. "←/Esc"
+    pub fn label(&self, action: Action, limit: usize) -> String {
+        self.keys
+            .get(&action)
+            .into_iter()
+            .flatten()
+            .take(limit)
+            .map(|&key| key_label(key))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Action bound to a key press. Ctrl and Alt chords are never remapped, and neither is
+    /// Shift on anything but a character, so chords like Shift+Up keep their fixed meaning.
+    pub fn action(&self, key: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+            return None;
+        }
+        if modifiers.contains(KeyModifiers::SHIFT) && !matches!(key, KeyCode::Char(_)) {
+            return None;
+        }
+        self.bindings.get(&key).copied()
+    }
+}
+
+/// Load a TOML keymap of `action = "keys"` entries. Each value is a comma separated list of
+/// single characters or key names; an action listed in the file loses its default keys, and
+/// actions not listed keep them.
+pub fn load_keymap_file(path: &Path) -> Result<Keymap> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read keymap file {}", path.display()))?;
+    parse_keymap(&contents, path)
+}
+
+/// Build a keymap from the contents of the keymap file at `path`
+fn parse_keymap(contents: &str, path: &Path) -> Result<Keymap> {
+    let entries: BTreeMap<String, String> = toml::from_str(contents)
+        .with_context(|| format!("Invalid keymap file {}", path.display()))?;
+
+    let mut overrides: HashMap<Action, Vec<KeyCode>> = HashMap::new();
+    for (name, value) in &entries {
+        let action = Action::ALL
+            .into_iter()
+            .find(|action| action.name() == name)
+            .ok_or_else(|| anyhow!("{}: unknown action `{}`", path.display(), name))?;
+        let keys = value
+            .split(',')
+            .map(parse_key)
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("{}: invalid keys for `{}`", path.display(), name))?;
+        overrides.insert(action, keys);
+    }
+
+    Keymap::from_keys(|action| overrides.remove(&action).unwrap_or_else(|| action.default_keys().to_vec()))
+        .with_context(|| format!("{}: conflicting bindings", path.display()))
+}
+
+/// Parse a single character (`x`) or a key name (`Up`, `Enter`, `Space`, ...)
+fn parse_key(key: &str) -> Result<KeyCode> {
+    // A lone space is a valid key, so only trim around longer values
+    let key = if key == " " { key } else { key.trim() };
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+    match key.to_ascii_lowercase().as_str() {
+        "up" => Ok(KeyCode::Up),
+        "down" => Ok(KeyCode::Down),
+        "left" => Ok(KeyCode::Left),
+        "right" => Ok(KeyCode::Right),
+        "enter" => Ok(KeyCode::Enter),
+        "esc" | "escape" => Ok(KeyCode::Esc),
+        "backspace" => Ok(KeyCode::Backspace),
+        "tab" => Ok(KeyCode::Tab),
+        "delete" | "del" => Ok(KeyCode::Delete),
+        "space" => Ok(KeyCode::Char(' ')),
+        "comma" => Ok(KeyCode::Char(',')),
+        "" => Err(anyhow!("empty key")),
+        _ => Err(anyhow!("unknown key `{}`", key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Result<Keymap> {
+        parse_keymap(contents, Path::new("keys.toml"))
+    }
+
+    #[test]
+    fn parse_key_accepts_characters_and_key_names() {
+        assert_eq!(parse_key("x").unwrap(), KeyCode::Char('x'));
+        assert_eq!(parse_key(" X ").unwrap(), KeyCode::Char('X'));
+        assert_eq!(parse_key(" ").unwrap(), KeyCode::Char(' '));
+        assert_eq!(parse_key("Up").unwrap(), KeyCode::Up);
+        assert_eq!(parse_key("ESCAPE").unwrap(), KeyCode::Esc);
+        assert_eq!(parse_key("space").unwrap(), KeyCode::Char(' '));
+        assert_eq!(parse_key("comma").unwrap(), KeyCode::Char(','));
+        assert!(parse_key("").is_err());
+        assert!(parse_key("PageUpp").is_err());
+    }
+
+    #[test]
+    fn listed_actions_replace_their_defaults_and_others_keep_them() {
+        let keymap = parse("# vim-less layout\nnavigate_up = \"w, Up\"\nquit = \"x\"\n").unwrap();
+
+        assert_eq!(keymap.action(KeyCode::Char('w'), KeyModifiers::NONE), Some(Action::NavigateUp));
+        assert_eq!(keymap.action(KeyCode::Up, KeyModifiers::NONE), Some(Action::NavigateUp));
+        assert_eq!(keymap.action(KeyCode::Char('k'), KeyModifiers::NONE), None);
+        assert_eq!(keymap.action(KeyCode::Char('x'), KeyModifiers::NONE), Some(Action::Quit));
+        assert_eq!(keymap.action(KeyCode::Char('q'), KeyModifiers::NONE), None);
+        assert_eq!(keymap.action(KeyCode::Char('j'), KeyModifiers::NONE), Some(Action::NavigateDown));
+        assert_eq!(keymap.action(KeyCode::Char('d'), KeyModifiers::NONE), Some(Action::Delete));
+    }
+
+    #[test]
+    fn empty_file_keeps_the_default_keys() {
+        let keymap = parse("").unwrap();
+        let defaults = Keymap::default();
+
+        for action in Action::ALL {
+            for &key in action.default_keys() {
+                assert_eq!(keymap.action(key, KeyModifiers::NONE), Some(action));
+                assert_eq!(defaults.action(key, KeyModifiers::NONE), Some(action));
+            }
+        }
+    }
+
+    #[test]
+    fn binding_one_key_to_two_actions_is_an_error() {
+        // `j` still belongs to navigate_down, which is not overridden
+        let error = parse("sort = \"j\"\n").unwrap_err();
+        let message = format!("{:#}", error);
+        assert!(message.contains("conflicting bindings"), "{}", message);
+        assert!(message.contains("navigate_down") && message.contains("sort"), "{}", message);
+    }
+
+    #[test]
+    fn keys_of_fixed_commands_cannot_be_bound() {
+        for contents in ["navigate_up = \"c, Up\"\n", "navigate_down = \"t\"\n", "back = \"g\"\n", "quit = \"/\"\n"] {
+            let error = parse(contents).err().unwrap();
+            assert!(format!("{:#}", error).contains("built-in command"), "{}", contents);
+        }
+    }
+
+    #[test]
+    fn labels_follow_the_bound_keys() {
+        let defaults = Keymap::default();
+        assert_eq!(defaults.label(Action::Back, 2), "←/Esc");
+        assert_eq!(defaults.label(Action::Forward, 2), "→/Enter");
+
+        let keymap = parse("sort = \"o\"\nnavigate_up = \"w, Up\"\n").unwrap();
+        assert_eq!(keymap.label(Action::Sort, 1), "o");
+        assert_eq!(keymap.label(Action::NavigateUp, usize::MAX), "w/↑");
+    }
+
+    #[test]
+    fn unknown_actions_and_unquoted_values_are_rejected() {
+        assert!(parse("jump = \"x\"\n").is_err());
+        assert!(parse("quit = x\n").is_err());
+        assert!(parse("quit \"x\"\n").is_err());
+    }
+
+    #[test]
+    fn ctrl_and_alt_chords_are_never_remapped() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action(KeyCode::Char('q'), KeyModifiers::CONTROL), None);
+        assert_eq!(keymap.action(KeyCode::Char('q'), KeyModifiers::ALT), None);
+        assert_eq!(keymap.action(KeyCode::Up, KeyModifiers::SHIFT), None);
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{Context, Result};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Appends log records to a file; the TUI owns the screen so nothing can go to stderr
+struct FileLogger {
+    file: Mutex<File>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or_default();
+        if let Ok(mut file) = self.file.lock() {
+            let _ = writeln!(file, "{:.3} {:<5} {}", timestamp, record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Send `log` output to `path`, appending so earlier sessions are kept for bug reports
+pub fn init(path: &Path) -> Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file {}", path.display()))?;
+
+    log::set_boxed_logger(Box::new(FileLogger { file: Mutex::new(file) }))
+        .context("Logger already initialized")?;
+    log::set_max_level(LevelFilter::Debug);
+    log::info!("zfs_space_visualizer {} started", env!("CARGO_PKG_VERSION"));
+    Ok(())
+}
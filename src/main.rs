@@ -1,50 +1,46 @@
 mod app;
+mod build_info;
 mod zfs;
 mod ui;
 mod state;
 mod navigation;
 mod data;
+mod dataset_tree;
 mod sorting;
 mod theme;
+mod keymap;
 mod config;
 mod update;
+mod clipboard;
+mod fixture;
+mod logging;
+mod report;
+mod terminal;
 
 use anyhow::Result;
-use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::{backend::CrosstermBackend, Terminal};
-use std::io;
+use std::io::IsTerminal;
 
 use app::App;
 use config::{Config, Commands};
-
-struct TerminalGuard;
-
-impl Drop for TerminalGuard {
-    fn drop(&mut self) {
-        let _ = disable_raw_mode();
-        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
-    }
-}
-
-fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    Ok(Terminal::new(backend)?)
-}
+use terminal::{install_panic_hook, setup_terminal, TerminalGuard};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command line arguments
     let config = Config::parse_args();
 
+    if config.version_verbose {
+        for line in build_info::describe() {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
     // Handle update command before validating config or starting TUI
-    if let Some(Commands::Update) = &config.command {
+    if let Some(Commands::Update { rollback }) = &config.command {
+        if *rollback {
+            return update::rollback();
+        }
         return update::check_and_update().await;
     }
 
@@ -54,6 +50,37 @@ async fn main() -> Result<()> {
         std::process::exit(1);
     }
 
+    if let Some(path) = &config.log_file
+        && let Err(e) = logging::init(path) {
+            eprintln!("Log file error: {:#}", e);
+            std::process::exit(1);
+        }
+
+    if let Some(path) = &config.fixture
+        && let Err(e) = fixture::load(path) {
+            eprintln!("Fixture error: {:#}", e);
+            std::process::exit(1);
+        }
+
+    // Check for ZFS before touching the terminal so a missing install fails cleanly
+    if !fixture::is_active() && zfs::find_missing_command().is_some() {
+        eprintln!("ZFS utilities not found; is ZFS installed?");
+        std::process::exit(1);
+    }
+
+    if let Some(Commands::Report { alarm_threshold }) = &config.command {
+        let code = report::run(&zfs::TokioCommandRunner::new(config.command_timeout), config.units, *alarm_threshold).await?;
+        std::process::exit(code);
+    }
+
+    // Raw mode and the alternate screen would only garble a pipe or file; print the report instead
+    if !std::io::stdout().is_terminal() {
+        let code = report::run(&zfs::TokioCommandRunner::new(config.command_timeout), config.units, None).await?;
+        std::process::exit(code);
+    }
+
+    terminal::set_mouse_capture(!config.no_mouse);
+    install_panic_hook();
     let _guard = TerminalGuard;
     let mut terminal = setup_terminal()?;
 
@@ -1,10 +1,15 @@
 use anyhow::Result;
-use crossterm::event::{KeyCode, KeyModifiers};
+use std::time::{Duration, Instant};
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
-use crate::state::{AppState, AppView};
+use crate::keymap::Action;
+use crate::state::{AppState, AppView, InputAction, Modal, TextInput};
 
-const PAGE_SIZE: usize = 10;
-pub const DELETE_CONFIRMATION_TIMEOUT_SECS: u64 = 3;
+/// Page size used before the first draw has recorded a list area
+const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// How long after the last keystroke further keys extend the type-ahead buffer
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_secs(1);
 
 pub struct Navigator;
 
@@ -21,31 +26,108 @@ impl Navigator {
             // If we're just clearing an error, don't process other key actions
             return Ok(());
         }
+
+        // Informational messages only last until the next key press
+        state.info_message = None;
+
+        // Any key completes or cancels a pending multi-key sequence
+        let pending_key = state.pending_key.take();
+        // Only a 'q' straight after the first one confirms quitting
+        let quit_confirmed = state.quit_confirmation_timestamp.take().is_some();
+
+        if state.input.is_some() {
+            Self::handle_input_key(state, key, modifiers).await;
+            return Ok(());
+        }
+
+        if state.modal.is_some() {
+            Self::handle_modal_key(state, key, modifiers, quit_confirmed);
+            return Ok(());
+        }
+
         match &state.current_view {
             AppView::Help => {
+                if let Some(action) = state.keymap.action(key, modifiers) {
+                    match action {
+                        Action::Quit => state.request_quit(quit_confirmed),
+                        Action::Back => Self::go_back(state).await?,
+                        Action::NavigateUp => state.theme_manager.previous_theme(),
+                        Action::NavigateDown => state.theme_manager.next_theme(),
+                        Action::Forward => state.theme_manager.select_theme(),
+                        Action::Delete | Action::Sort | Action::Help => {}
+                    }
+                    return Ok(());
+                }
                 match key {
-                    KeyCode::Char('q') => state.should_quit = true,
                     KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => state.should_quit = true,
-                    KeyCode::Esc | KeyCode::Backspace | KeyCode::Left => Self::go_back(state).await?,
-                    KeyCode::Up => state.theme_manager.previous_theme(),
-                    KeyCode::Down => state.theme_manager.next_theme(),
-                    KeyCode::Enter | KeyCode::Right => state.theme_manager.select_theme(),
+                    // Help text scrolling is clamped against the visible height when drawn
+                    KeyCode::Up if modifiers.contains(KeyModifiers::SHIFT) => {
+                        state.help_scroll_offset = state.help_scroll_offset.saturating_sub(1);
+                    }
+                    KeyCode::Down if modifiers.contains(KeyModifiers::SHIFT) => state.help_scroll_offset = state.help_scroll_offset.saturating_add(1),
+                    KeyCode::PageUp => state.help_scroll_offset = state.help_scroll_offset.saturating_sub(Self::page_size(state)),
+                    KeyCode::PageDown => state.help_scroll_offset = state.help_scroll_offset.saturating_add(Self::page_size(state)),
+                    KeyCode::Home => state.help_scroll_offset = 0,
+                    KeyCode::End => state.help_scroll_offset = usize::MAX,
                     _ => {}
                 }
             }
             _ => {
+                // While typing a name, letters extend the prefix instead of running commands
+                let typing = Self::type_ahead_active(state) && matches!(key, KeyCode::Char(_));
+                if let Some(action) = state.keymap.action(key, modifiers).filter(|_| !typing) {
+                    match action {
+                        Action::Quit => state.request_quit(quit_confirmed),
+                        Action::Help => Self::show_help(state),
+                        Action::Sort => Self::toggle_sort(state, false),
+                        Action::Delete if !state.config.readonly => Self::handle_delete_key(state).await?,
+                        Action::Delete => {}
+                        Action::Back => Self::go_back(state).await?,
+                        Action::Forward => Self::go_forward(state).await?,
+                        Action::NavigateUp => Self::previous_item(state),
+                        Action::NavigateDown => Self::next_item(state),
+                    }
+                    return Ok(());
+                }
                 match key {
-                    KeyCode::Char('q') => state.should_quit = true,
+                    // '/' starts a jump explicitly, so names beginning with a command key work too
+                    KeyCode::Char('/') if matches!(state.current_view, AppView::DatasetView(_)) => {
+                        Self::start_type_ahead(state);
+                    }
+                    KeyCode::Char(c) if Self::type_ahead_active(state) && !modifiers.contains(KeyModifiers::CONTROL) => {
+                        Self::type_ahead(state, c);
+                    }
                     KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => state.should_quit = true,
-                    KeyCode::Char('h') => Self::show_help(state),
-                    KeyCode::Char('s') => Self::toggle_sort(state),
-                    KeyCode::Char('d') if !state.config.readonly => Self::handle_delete_key(state).await?,
-                    KeyCode::Esc | KeyCode::Backspace | KeyCode::Left => Self::go_back(state).await?,
-                    KeyCode::Enter | KeyCode::Right => Self::go_forward(state).await?,
-                    KeyCode::Up => Self::previous_item(state),
-                    KeyCode::Down => Self::next_item(state),
+                    KeyCode::Char('S') => Self::toggle_sort(state, true),
+                    KeyCode::Char('i') => Self::show_details(state).await,
+                    KeyCode::Char('p') => Self::show_pool_status(state).await,
+                    KeyCode::Char('I') => Self::show_pool_iostat(state),
+                    KeyCode::Char('e') => Self::show_error_log(state),
+                    KeyCode::Char('V') => Self::show_about(state),
+                    KeyCode::Char('f') => Self::show_snapshot_diff(state).await,
+                    KeyCode::Char('y') if state.delete_confirmation_pending => Self::copy_pending_commands(state).await,
+                    KeyCode::Char('y') => Self::copy_selected_name(state).await,
+                    KeyCode::Char('b') => state.show_usage_breakdown = !state.show_usage_breakdown,
+                    KeyCode::Char('u') => state.show_usable_capacity = !state.show_usable_capacity,
+                    KeyCode::Char('v') => state.scale_to_pool = !state.scale_to_pool,
+                    KeyCode::Char(' ') => Self::toggle_mark(state),
+                    KeyCode::Char('t') => Self::toggle_tree_view(state),
+                    KeyCode::Char('H') => Self::toggle_hide_inactive(state),
+                    KeyCode::Char('c') if !state.config.readonly => Self::prompt_create_dataset(state),
+                    KeyCode::Char('X') if !state.config.readonly => Self::prompt_destroy_dataset(state),
+                    KeyCode::Char('r') if !state.config.readonly => Self::prompt_rename_snapshot(state),
+                    KeyCode::Char('P') if !state.config.readonly => Self::prompt_prune_snapshots(state),
+                    KeyCode::Char('!') if !state.config.readonly => Self::prompt_shell_command(state),
+                    KeyCode::Char('a') if matches!(state.current_view, AppView::PoolList) => Self::show_all_snapshots(state),
+                    KeyCode::Char('[') => Self::switch_pool(state, false).await?,
+                    KeyCode::Char(']') => Self::switch_pool(state, true).await?,
+                    KeyCode::Char('g') if pending_key == Some('g') => Self::first_item(state),
+                    KeyCode::Char('g') => state.pending_key = Some('g'),
+                    KeyCode::Char('G') => Self::last_item(state),
                     KeyCode::PageUp => Self::page_up(state),
                     KeyCode::PageDown => Self::page_down(state),
+                    KeyCode::Home => Self::first_item(state),
+                    KeyCode::End => Self::last_item(state),
                     _ => {}
                 }
             }
@@ -53,11 +135,358 @@ impl Navigator {
         Ok(())
     }
 
+    pub fn handle_mouse_event(state: &mut AppState, mouse: MouseEvent) {
+        // Mouse input is ignored while an error is waiting to be acknowledged
+        if state.error_message.is_some() {
+            return;
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollUp => Self::scroll(state, false),
+            MouseEventKind::ScrollDown => Self::scroll(state, true),
+            MouseEventKind::Down(MouseButton::Left) if state.modal.is_none() => {
+                Self::select_row(state, mouse.row);
+            }
+            _ => {}
+        }
+    }
+
+    fn scroll(state: &mut AppState, down: bool) {
+        if let Some(modal) = state.modal.as_mut() {
+            modal.scroll_offset = if down {
+                (modal.scroll_offset + 1).min(modal.lines.len().saturating_sub(1))
+            } else {
+                modal.scroll_offset.saturating_sub(1)
+            };
+            return;
+        }
+
+        match (&state.current_view, down) {
+            (AppView::Help, true) => state.help_scroll_offset = state.help_scroll_offset.saturating_add(1),
+            (AppView::Help, false) => state.help_scroll_offset = state.help_scroll_offset.saturating_sub(1),
+            (_, true) => Self::next_item(state),
+            (_, false) => Self::previous_item(state),
+        }
+    }
+
+    /// Select the list row under a clicked screen row, accounting for the border and scroll offset
+    fn select_row(state: &mut AppState, row: u16) {
+        let area = state.list_area;
+        if row <= area.y || row >= area.y + area.height.saturating_sub(1) {
+            return;
+        }
+        let visible_row = (row - area.y - 1) as usize;
+        let visible_height = area.height.saturating_sub(2) as usize;
+
+        match &state.current_view {
+            AppView::PoolList => {
+                // The pool list scrolls just far enough to keep the selection on screen
+                let offset = (state.selected_pool_index + 1).saturating_sub(visible_height);
+                let index = offset + visible_row;
+                if index < state.data_manager.pools.len() {
+                    state.selected_pool_index = index;
+                }
+            }
+            AppView::DatasetView(_) => {
+                let index = state.dataset_scroll_offset + visible_row;
+                if index < state.data_manager.datasets.len() {
+                    state.selected_dataset_index = index;
+                }
+            }
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => {
+                let index = state.snapshot_scroll_offset + visible_row;
+                if index < state.data_manager.snapshots.len() {
+                    state.selected_snapshot_index = index;
+                }
+            }
+            AppView::Help => {}
+        }
+    }
+
+    async fn handle_input_key(state: &mut AppState, key: KeyCode, modifiers: KeyModifiers) {
+        let Some(input) = state.input.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => state.input = None,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                state.input = None;
+                state.should_quit = true;
+            }
+            KeyCode::Backspace => {
+                input.value.pop();
+            }
+            KeyCode::Char(c) => input.value.push(c),
+            KeyCode::Enter => {
+                if let Some(input) = state.input.take() {
+                    Self::submit_input(state, input).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn submit_input(state: &mut AppState, input: TextInput) {
+        let value = input.value.trim();
+        if value.is_empty() {
+            return;
+        }
+        match input.action {
+            InputAction::CreateDataset { parent } => {
+                let dataset_name = format!("{}/{}", parent, value);
+                if let Err(e) = crate::zfs::create_dataset(&state.data_manager.runner, &dataset_name).await {
+                    state.set_error(format!("{:#}", e));
+                    return;
+                }
+                Self::reload_datasets(state).await;
+                if let Some(index) = state.data_manager.datasets.iter().position(|d| d.name == dataset_name) {
+                    state.selected_dataset_index = index;
+                }
+                state.set_info(format!("Created {}", dataset_name));
+            }
+            InputAction::DestroyDataset { name, recursive } => {
+                let expected = if recursive { "destroy children" } else { name.as_str() };
+                if value != expected {
+                    state.set_info(format!("Confirmation did not match; {} was not destroyed", name));
+                    return;
+                }
+
+                // Children or snapshots need their own confirmation before going recursive
+                if !recursive && Self::dataset_has_children(state, &name) {
+                    state.input = Some(TextInput::new(
+                        format!("{} has children or snapshots. Type 'destroy children' to destroy them all", name),
+                        String::new(),
+                        InputAction::DestroyDataset { name, recursive: true },
+                    ));
+                    return;
+                }
+
+                if let Err(e) = crate::zfs::destroy_dataset(&state.data_manager.runner, &name, recursive, state.config.dry_run).await {
+                    state.set_error(format!("{:#}", e));
+                    return;
+                }
+                if state.config.dry_run {
+                    state.set_info(format!("Dry run: would destroy {}", name));
+                    return;
+                }
+                state.data_manager.invalidate_snapshots(&name);
+                Self::reload_datasets(state).await;
+                state.selected_dataset_index = state
+                    .selected_dataset_index
+                    .min(state.data_manager.datasets.len().saturating_sub(1));
+                state.set_info(format!("Destroyed {}", name));
+            }
+            InputAction::RenameSnapshot { old } => Self::rename_snapshot(state, old, value).await,
+            InputAction::ShellCommand => state.pending_shell_command = Some(value.to_string()),
+            InputAction::PruneSnapshots => match value.parse::<u64>() {
+                Ok(days) => Self::mark_snapshots_older_than(state, days),
+                Err(_) => state.set_error(format!("Not a number of days: {}", value)),
+            },
+        }
+    }
+
+    async fn rename_snapshot(state: &mut AppState, old: String, suffix: &str) {
+        if suffix.contains(['@', '/']) {
+            state.set_error("Snapshot names cannot contain '@' or '/'; only the part after '@' can change".to_string());
+            return;
+        }
+        let Some((dataset, _)) = old.split_once('@') else {
+            return;
+        };
+        let new_name = format!("{}@{}", dataset, suffix);
+        if new_name == old {
+            return;
+        }
+
+        if let Err(e) = crate::zfs::rename_snapshot(&state.data_manager.runner, &old, &new_name).await {
+            if format!("{:#}", e).contains("already exists") {
+                state.set_error(format!("A snapshot named {} already exists", new_name));
+            } else {
+                state.set_error(format!("{:#}", e));
+            }
+            return;
+        }
+
+        if state.selected_snapshots.remove(&old) {
+            state.selected_snapshots.insert(new_name.clone());
+        }
+        if let AppView::SnapshotDetail(_, dataset_name) = &state.current_view {
+            let dataset_name = dataset_name.clone();
+            if let Err(e) = state.data_manager.reload_snapshots(&dataset_name).await {
+                state.set_error(format!("Failed to reload snapshots: {:#}", e));
+                return;
+            }
+            state.sort_manager.sort_snapshots(&mut state.data_manager.snapshots);
+        }
+        if let Some(index) = state.data_manager.snapshots.iter().position(|s| s.name == new_name) {
+            state.selected_snapshot_index = index;
+        }
+        state.set_info(format!("Renamed {} to {}", old, new_name));
+    }
+
+    /// Open a prompt prefilled with the selected snapshot's name after `@`
+    fn prompt_rename_snapshot(state: &mut AppState) {
+        let AppView::SnapshotDetail(_, _) = &state.current_view else {
+            return;
+        };
+        let Some(old) = state.data_manager.snapshots.get(state.selected_snapshot_index).map(|s| s.name.clone()) else {
+            return;
+        };
+        let Some((dataset, suffix)) = old.split_once('@') else {
+            return;
+        };
+        state.input = Some(
+            TextInput::new("Rename snapshot".to_string(), format!("{}@", dataset), InputAction::RenameSnapshot {
+                old: old.clone(),
+            })
+            .with_value(suffix.to_string()),
+        );
+    }
+
+    fn prompt_prune_snapshots(state: &mut AppState) {
+        let AppView::SnapshotDetail(_, _) = &state.current_view else {
+            return;
+        };
+        state.input = Some(TextInput::new(
+            "Mark snapshots older than how many days?".to_string(),
+            String::new(),
+            InputAction::PruneSnapshots,
+        ));
+    }
+
+    /// Replace the marks with every snapshot created more than `days` ago, so they can be
+    /// reviewed in the list and removed with the usual batch delete
+    fn mark_snapshots_older_than(state: &mut AppState, days: u64) {
+        let cutoff = crate::zfs::days_ago(days);
+
+        // Snapshots without a known creation time are never marked
+        let old: Vec<&crate::zfs::Snapshot> = state
+            .data_manager
+            .snapshots
+            .iter()
+            .filter(|s| s.creation.is_some_and(|creation| creation < cutoff))
+            .collect();
+        let used: u64 = old.iter().map(|s| s.used).sum();
+        state.selected_snapshots = old.iter().map(|s| s.name.clone()).collect();
+
+        if state.selected_snapshots.is_empty() {
+            state.set_info(format!("No snapshots older than {} days", days));
+        } else {
+            state.set_info(format!(
+                "Marked {} snapshots older than {} days ({} used); review them, then press d twice to delete",
+                state.selected_snapshots.len(),
+                days,
+                crate::zfs::format_bytes(used, state.config.units)
+            ));
+        }
+    }
+
+    /// Open a prompt with a shell command for the selection, to be edited and run outside the TUI
+    fn prompt_shell_command(state: &mut AppState) {
+        let command = match &state.current_view {
+            AppView::PoolList => state.data_manager.pools.get(state.selected_pool_index).map(|p| format!("zpool status {}", p.name)),
+            AppView::DatasetView(_) => state.data_manager.datasets.get(state.selected_dataset_index).map(|d| format!("zfs get all {}", d.name)),
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => state.data_manager.snapshots.get(state.selected_snapshot_index).map(|s| format!("zfs send {}", s.name)),
+            AppView::Help => None,
+        };
+        state.input = Some(
+            TextInput::new("Run in shell".to_string(), "$ ".to_string(), InputAction::ShellCommand)
+                .with_value(command.unwrap_or_default()),
+        );
+    }
+
+    /// Whether the dataset has descendant datasets or snapshots of its own, as far as is loaded
+    fn dataset_has_children(state: &AppState, name: &str) -> bool {
+        let child_prefix = format!("{}/", name);
+        let has_child_datasets = state.data_manager.pool_datasets.iter().any(|d| d.name.starts_with(&child_prefix));
+        let has_snapshots = state
+            .data_manager
+            .pool_datasets
+            .iter()
+            .find(|d| d.name == name)
+            .is_some_and(|d| d.snapshot_bytes() > 0)
+            || state.data_manager.get_cached_snapshots(name).is_some_and(|snapshots| !snapshots.is_empty());
+        has_child_datasets || has_snapshots
+    }
+
+    /// Ask for the selected dataset's name to be typed back before destroying it
+    fn prompt_destroy_dataset(state: &mut AppState) {
+        let AppView::DatasetView(_) = &state.current_view else {
+            return;
+        };
+        let Some(name) = state.data_manager.datasets.get(state.selected_dataset_index).map(|d| d.name.clone()) else {
+            return;
+        };
+        if !name.contains('/') {
+            state.set_info("The root dataset can only be removed by destroying the pool".to_string());
+            return;
+        }
+        state.input = Some(TextInput::new(
+            format!("Type {} to destroy it", name),
+            String::new(),
+            InputAction::DestroyDataset { name, recursive: false },
+        ));
+    }
+
+    /// Ask for the name of a new child of the selected dataset
+    fn prompt_create_dataset(state: &mut AppState) {
+        let AppView::DatasetView(_) = &state.current_view else {
+            return;
+        };
+        let Some(parent) = state.data_manager.datasets.get(state.selected_dataset_index).map(|d| d.name.clone()) else {
+            return;
+        };
+        state.input = Some(TextInput::new(
+            "Create dataset".to_string(),
+            format!("{}/", parent),
+            InputAction::CreateDataset { parent },
+        ));
+    }
+
+    async fn reload_datasets(state: &mut AppState) {
+        let AppView::DatasetView(pool_name) = &state.current_view else {
+            return;
+        };
+        let pool_name = pool_name.clone();
+        if let Err(e) = state.data_manager.load_datasets(&pool_name).await {
+            state.set_error(format!("Failed to reload datasets: {:#}", e));
+            return;
+        }
+        state.arrange_datasets();
+    }
+
+    fn handle_modal_key(state: &mut AppState, key: KeyCode, modifiers: KeyModifiers, quit_confirmed: bool) {
+        let page_size = Self::page_size(state);
+        let Some(modal) = state.modal.as_mut() else {
+            return;
+        };
+        // Scrolling past the end is clamped against the visible height when drawn
+        let last_line = modal.lines.len().saturating_sub(1);
+        if let Some(action) = state.keymap.action(key, modifiers) {
+            match action {
+                Action::Quit => state.request_quit(quit_confirmed),
+                Action::Back | Action::Forward => state.modal = None,
+                Action::NavigateUp => modal.scroll_offset = modal.scroll_offset.saturating_sub(1),
+                Action::NavigateDown => modal.scroll_offset = (modal.scroll_offset + 1).min(last_line),
+                Action::Delete | Action::Sort | Action::Help => {}
+            }
+            return;
+        }
+        match key {
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => state.should_quit = true,
+            KeyCode::PageUp => modal.scroll_offset = modal.scroll_offset.saturating_sub(page_size),
+            KeyCode::PageDown => modal.scroll_offset = (modal.scroll_offset + page_size).min(last_line),
+            KeyCode::Home => modal.scroll_offset = 0,
+            KeyCode::End => modal.scroll_offset = last_line,
+            _ => {}
+        }
+    }
+
     fn previous_item(state: &mut AppState) {
         match &state.current_view {
             AppView::PoolList => state.selected_pool_index = state.selected_pool_index.saturating_sub(1),
             AppView::DatasetView(_) => state.selected_dataset_index = state.selected_dataset_index.saturating_sub(1),
-            AppView::SnapshotDetail(_, _) => state.selected_snapshot_index = state.selected_snapshot_index.saturating_sub(1),
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => state.selected_snapshot_index = state.selected_snapshot_index.saturating_sub(1),
             AppView::Help => {}
         }
     }
@@ -70,61 +499,163 @@ impl Navigator {
             AppView::DatasetView(_) => {
                 state.selected_dataset_index = (state.selected_dataset_index + 1).min(state.data_manager.datasets.len().saturating_sub(1));
             }
-            AppView::SnapshotDetail(_, _) => {
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => {
                 state.selected_snapshot_index = (state.selected_snapshot_index + 1).min(state.data_manager.snapshots.len().saturating_sub(1));
             }
             AppView::Help => {}
         }
     }
 
+    fn type_ahead_active(state: &AppState) -> bool {
+        matches!(state.current_view, AppView::DatasetView(_))
+            && state
+                .type_ahead_timestamp
+                .is_some_and(|timestamp| timestamp.elapsed() < TYPE_AHEAD_TIMEOUT)
+    }
+
+    /// Open an empty type-ahead buffer; following keys extend it until it times out
+    fn start_type_ahead(state: &mut AppState) {
+        state.type_ahead.clear();
+        state.type_ahead_timestamp = Some(Instant::now());
+        state.set_info("Jump: ".to_string());
+    }
+
+    /// Extend the type-ahead buffer and select the first dataset whose short name starts with it
+    fn type_ahead(state: &mut AppState, c: char) {
+        state.type_ahead.push(c);
+        state.type_ahead_timestamp = Some(Instant::now());
+
+        let prefix = state.type_ahead.to_lowercase();
+        let found = state.data_manager.datasets.iter().position(|dataset| {
+            let short_name = dataset.name.rsplit('/').next().unwrap_or(&dataset.name);
+            short_name.to_lowercase().starts_with(&prefix)
+        });
+
+        match found {
+            Some(index) => {
+                state.selected_dataset_index = index;
+                state.set_info(format!("Jump: {}", state.type_ahead));
+            }
+            None => state.set_info(format!("Jump: {} (no match)", state.type_ahead)),
+        }
+    }
+
+    /// Rows per page: `--page-size` if given, otherwise the height of the list last drawn
+    fn page_size(state: &AppState) -> usize {
+        state.config.page_size.unwrap_or_else(|| {
+            match state.list_area.height.saturating_sub(2) as usize {
+                0 => DEFAULT_PAGE_SIZE,
+                rows => rows,
+            }
+        })
+    }
+
     fn page_up(state: &mut AppState) {
+        let page_size = Self::page_size(state);
         match &state.current_view {
             AppView::PoolList => {
-                state.selected_pool_index = state.selected_pool_index.saturating_sub(PAGE_SIZE);
+                state.selected_pool_index = state.selected_pool_index.saturating_sub(page_size);
             }
             AppView::DatasetView(_) => {
-                state.selected_dataset_index = state.selected_dataset_index.saturating_sub(PAGE_SIZE);
+                state.selected_dataset_index = state.selected_dataset_index.saturating_sub(page_size);
             }
-            AppView::SnapshotDetail(_, _) => {
-                state.selected_snapshot_index = state.selected_snapshot_index.saturating_sub(PAGE_SIZE);
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => {
+                state.selected_snapshot_index = state.selected_snapshot_index.saturating_sub(page_size);
             }
             AppView::Help => {}
         }
     }
 
     fn page_down(state: &mut AppState) {
+        let page_size = Self::page_size(state);
         match &state.current_view {
             AppView::PoolList => {
-                state.selected_pool_index = (state.selected_pool_index + PAGE_SIZE).min(state.data_manager.pools.len().saturating_sub(1));
+                state.selected_pool_index = (state.selected_pool_index + page_size).min(state.data_manager.pools.len().saturating_sub(1));
             }
             AppView::DatasetView(_) => {
-                state.selected_dataset_index = (state.selected_dataset_index + PAGE_SIZE).min(state.data_manager.datasets.len().saturating_sub(1));
+                state.selected_dataset_index = (state.selected_dataset_index + page_size).min(state.data_manager.datasets.len().saturating_sub(1));
             }
-            AppView::SnapshotDetail(_, _) => {
-                state.selected_snapshot_index = (state.selected_snapshot_index + PAGE_SIZE).min(state.data_manager.snapshots.len().saturating_sub(1));
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => {
+                state.selected_snapshot_index = (state.selected_snapshot_index + page_size).min(state.data_manager.snapshots.len().saturating_sub(1));
+            }
+            AppView::Help => {}
+        }
+    }
+
+    fn first_item(state: &mut AppState) {
+        match &state.current_view {
+            AppView::PoolList => state.selected_pool_index = 0,
+            AppView::DatasetView(_) => state.selected_dataset_index = 0,
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => state.selected_snapshot_index = 0,
+            AppView::Help => {}
+        }
+    }
+
+    fn last_item(state: &mut AppState) {
+        match &state.current_view {
+            AppView::PoolList => {
+                state.selected_pool_index = state.data_manager.pools.len().saturating_sub(1);
+            }
+            AppView::DatasetView(_) => {
+                state.selected_dataset_index = state.data_manager.datasets.len().saturating_sub(1);
+            }
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => {
+                state.selected_snapshot_index = state.data_manager.snapshots.len().saturating_sub(1);
             }
             AppView::Help => {}
         }
     }
 
+    /// Jump straight to a pool's dataset view, staying on the pool list if it does not exist
+    pub async fn open_pool(state: &mut AppState, pool_name: &str) -> Result<()> {
+        match state.data_manager.pools.iter().position(|p| p.name == pool_name) {
+            Some(index) => {
+                state.current_view = AppView::PoolList;
+                state.selected_pool_index = index;
+                Self::go_forward(state).await
+            }
+            None => {
+                state.set_error(format!("Pool '{}' not found", pool_name));
+                Ok(())
+            }
+        }
+    }
+
     async fn go_forward(state: &mut AppState) -> Result<()> {
         match &state.current_view {
             AppView::PoolList => {
                 if let Some(pool_name) = state.data_manager.pools.get(state.selected_pool_index).map(|p| p.name.clone()) {
                     state.current_view = AppView::DatasetView(pool_name.clone());
                     state.selected_dataset_index = 0;
-                    state.data_manager.load_datasets(&pool_name).await?;
-                    state.sort_manager.sort_datasets(&mut state.data_manager.datasets);
+                    if let Err(e) = state.data_manager.load_datasets(&pool_name).await {
+                        state.current_view = AppView::PoolList;
+                        state.set_error(format!("Failed to load datasets: {:#}", e));
+                        return Ok(());
+                    }
+                    state.arrange_datasets();
                     state.reset_dataset_selection();
+                    state.restore_dataset_position(&pool_name);
                 }
             }
             AppView::DatasetView(pool_name) => {
+                let pool_name = pool_name.clone();
                 if let Some(dataset_name) = state.data_manager.datasets.get(state.selected_dataset_index).map(|d| d.name.clone()) {
                     state.current_view = AppView::SnapshotDetail(pool_name.clone(), dataset_name.clone());
                     state.selected_snapshot_index = 0;
-                    state.data_manager.load_snapshots(&dataset_name).await?;
+                    state.selected_snapshots.clear();
+                    if let Err(e) = state.data_manager.load_snapshots(&dataset_name).await {
+                        state.current_view = AppView::DatasetView(pool_name);
+                        state.set_error(format!("Failed to load snapshots: {:#}", e));
+                        return Ok(());
+                    }
                     state.sort_manager.sort_snapshots(&mut state.data_manager.snapshots);
                     state.reset_snapshot_selection();
+                    state.restore_snapshot_position(&dataset_name);
+                }
+            }
+            AppView::AllSnapshots => {
+                if let Some(snapshot_name) = state.data_manager.snapshots.get(state.selected_snapshot_index).map(|s| s.name.clone()) {
+                    Self::open_snapshot(state, &snapshot_name).await?;
                 }
             }
             AppView::SnapshotDetail(_, _) => {
@@ -137,16 +668,83 @@ impl Navigator {
         Ok(())
     }
 
+    /// Drill down to a snapshot from outside its dataset: open its pool's dataset list with
+    /// the dataset selected, then its snapshot list with the snapshot selected
+    async fn open_snapshot(state: &mut AppState, snapshot_name: &str) -> Result<()> {
+        let Some((dataset_name, _)) = snapshot_name.split_once('@') else {
+            return Ok(());
+        };
+        let pool_name = dataset_name.split('/').next().unwrap_or(dataset_name).to_string();
+
+        if let Err(e) = state.data_manager.load_datasets(&pool_name).await {
+            state.set_error(format!("Failed to load datasets: {:#}", e));
+            return Ok(());
+        }
+        if let Some(index) = state.data_manager.pools.iter().position(|p| p.name == pool_name) {
+            state.selected_pool_index = index;
+        }
+        state.current_view = AppView::DatasetView(pool_name.clone());
+        state.arrange_datasets();
+        state.reset_dataset_selection();
+        let Some(index) = state.data_manager.datasets.iter().position(|d| d.name == dataset_name) else {
+            state.set_error(format!("Dataset {} is not listed; it may be hidden or folded", dataset_name));
+            return Ok(());
+        };
+        state.selected_dataset_index = index;
+
+        state.current_view = AppView::SnapshotDetail(pool_name.clone(), dataset_name.to_string());
+        state.selected_snapshot_index = 0;
+        state.selected_snapshots.clear();
+        if let Err(e) = state.data_manager.load_snapshots(dataset_name).await {
+            state.current_view = AppView::DatasetView(pool_name);
+            state.set_error(format!("Failed to load snapshots: {:#}", e));
+            return Ok(());
+        }
+        state.sort_manager.sort_snapshots(&mut state.data_manager.snapshots);
+        state.reset_snapshot_selection();
+        if let Some(index) = state.data_manager.snapshots.iter().position(|s| s.name == snapshot_name) {
+            state.selected_snapshot_index = index;
+        }
+        Ok(())
+    }
+
+    /// From a dataset view, show the next (or previous) pool's datasets, wrapping around
+    async fn switch_pool(state: &mut AppState, forward: bool) -> Result<()> {
+        let AppView::DatasetView(_) = &state.current_view else {
+            return Ok(());
+        };
+        let pool_count = state.data_manager.pools.len();
+        if pool_count < 2 {
+            return Ok(());
+        }
+
+        Self::go_back(state).await?;
+        state.selected_pool_index = if forward {
+            (state.selected_pool_index + 1) % pool_count
+        } else {
+            (state.selected_pool_index + pool_count - 1) % pool_count
+        };
+        Self::go_forward(state).await
+    }
+
     async fn go_back(state: &mut AppState) -> Result<()> {
         match &state.current_view {
             AppView::PoolList => {
                 // Can't go back further
             }
-            AppView::DatasetView(_) => {
+            AppView::DatasetView(pool_name) => {
+                let pool_name = pool_name.clone();
+                state.remember_dataset_position(&pool_name);
+                state.current_view = AppView::PoolList;
+            }
+            AppView::AllSnapshots => {
                 state.current_view = AppView::PoolList;
             }
-            AppView::SnapshotDetail(pool_name, _) => {
-                state.current_view = AppView::DatasetView(pool_name.clone());
+            AppView::SnapshotDetail(pool_name, dataset_name) => {
+                let (pool_name, dataset_name) = (pool_name.clone(), dataset_name.clone());
+                state.remember_snapshot_position(&dataset_name);
+                state.current_view = AppView::DatasetView(pool_name);
+                state.selected_snapshots.clear();
             }
             AppView::Help => {
                 if let Some(prev_view) = state.previous_view.take() {
@@ -159,28 +757,322 @@ impl Navigator {
         Ok(())
     }
 
+    /// List every snapshot the prefetch has cached, across all pools and datasets
+    fn show_all_snapshots(state: &mut AppState) {
+        state.data_manager.load_all_cached_snapshots();
+        state.sort_manager.sort_snapshots(&mut state.data_manager.snapshots);
+        state.selected_snapshots.clear();
+        state.reset_snapshot_selection();
+        state.current_view = AppView::AllSnapshots;
+    }
+
     fn show_help(state: &mut AppState) {
         state.previous_view = Some(state.current_view.clone());
         state.current_view = AppView::Help;
+        state.help_scroll_offset = 0;
         state.theme_manager.set_selected_index_from_theme();
     }
 
-    fn toggle_sort(state: &mut AppState) {
+    /// Show details of the selected item: exact byte counts for pools and snapshots, and
+    /// for datasets those followed by all their ZFS properties
+    async fn show_details(state: &mut AppState) {
+        let units = state.config.units;
+        let size = |label: &str, bytes: Option<u64>| match bytes {
+            Some(bytes) => format!("{:<14}{:>12}  {} bytes", label, crate::zfs::format_bytes(bytes, units), bytes),
+            None => format!("{:<14}{:>12}", label, "-"),
+        };
+
+        let (title, mut lines, dataset_name) = match &state.current_view {
+            AppView::PoolList => {
+                let Some(pool) = state.data_manager.pools.get(state.selected_pool_index) else {
+                    return;
+                };
+                let lines = vec![
+                    size("size", Some(pool.size)),
+                    size("allocated", Some(pool.allocated)),
+                    size("free", Some(pool.free)),
+                    size("usable used", pool.usable_used),
+                    size("usable size", pool.usable_size),
+                ];
+                (format!("Pool: {}", pool.name), lines, None)
+            }
+            AppView::DatasetView(_) => {
+                let Some(dataset) = state.data_manager.datasets.get(state.selected_dataset_index) else {
+                    return;
+                };
+                let mut lines = vec![
+                    size("used", Some(dataset.used)),
+                    size("referenced", Some(dataset.referenced)),
+                    size("snapshots", dataset.snapshot_used),
+                    size("children", Some(dataset.used_by_children)),
+                    size("available", dataset.available),
+                ];
+                // Counted from the unfolded listing so a collapsed row's totals are not added twice
+                let pool_datasets = &state.data_manager.pool_datasets;
+                if crate::dataset_tree::has_children(pool_datasets, &dataset.name) {
+                    let subtree = crate::dataset_tree::subtree_snapshot_bytes(pool_datasets, &dataset.name);
+                    lines.push(size("subtree snaps", Some(subtree)));
+                }
+                if dataset.reservation > 0 {
+                    lines.push(size("reservation", Some(dataset.reservation)));
+                }
+                if dataset.refreservation > 0 {
+                    lines.push(size("refreserv", Some(dataset.refreservation)));
+                }
+                // Reserved but unwritten space counts as used, which is why a near-empty volume can look full
+                if dataset.used_by_refreservation > 0 {
+                    lines.push(format!(
+                        "{:<14}{} of used is unwritten refreservation",
+                        "note",
+                        crate::zfs::format_bytes(dataset.used_by_refreservation, units)
+                    ));
+                }
+                if let Some(origin) = &dataset.origin {
+                    lines.push(format!("{:<14}{}", "clone of", origin));
+                }
+                (format!("Properties: {}", dataset.name), lines, Some(dataset.name.clone()))
+            }
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => {
+                let Some(snapshot) = state.data_manager.snapshots.get(state.selected_snapshot_index) else {
+                    return;
+                };
+                let mut lines = vec![
+                    size("used", Some(snapshot.used)),
+                    size("referenced", Some(snapshot.referenced)),
+                    size("written", Some(snapshot.written)),
+                ];
+                // Clones live in the snapshot's pool, whose datasets are already listed with their origin
+                let clones: Vec<&str> = state
+                    .data_manager
+                    .pool_datasets
+                    .iter()
+                    .filter(|d| d.origin.as_deref() == Some(snapshot.name.as_str()))
+                    .map(|d| d.name.as_str())
+                    .collect();
+                if !clones.is_empty() {
+                    lines.push(format!("{:<14}{}", "clones", clones.join(", ")));
+                }
+                (format!("Snapshot: {}", snapshot.name), lines, None)
+            }
+            AppView::Help => return,
+        };
+
+        if let Some(dataset_name) = dataset_name {
+            match crate::zfs::get_dataset_properties(&state.data_manager.runner, &dataset_name).await {
+                Ok(properties) => {
+                    let width = properties.iter().map(|(property, _)| property.len()).max().unwrap_or(0);
+                    lines.push(String::new());
+                    lines.extend(
+                        properties
+                            .iter()
+                            .map(|(property, value)| format!("{:<width$}  {}", property, value, width = width)),
+                    );
+                }
+                Err(e) => {
+                    state.set_error(format!("Failed to get dataset properties: {}", e));
+                    return;
+                }
+            }
+        }
+        state.modal = Some(Modal::new(title, lines));
+    }
+
+    async fn show_pool_status(state: &mut AppState) {
+        let AppView::PoolList = &state.current_view else {
+            return;
+        };
+        let Some(pool_name) = state.data_manager.pools.get(state.selected_pool_index).map(|p| p.name.clone()) else {
+            return;
+        };
+
+        match crate::zfs::get_pool_status(&state.data_manager.runner, &pool_name).await {
+            Ok(status) => {
+                let lines = status.lines().map(|line| line.to_string()).collect();
+                state.modal = Some(Modal::new(format!("Pool Status: {}", pool_name), lines));
+            }
+            Err(e) => state.set_error(format!("Failed to get pool status: {}", e)),
+        }
+    }
+
+    /// Show the recent errors, newest first, with how long ago each one happened
+    fn show_error_log(state: &mut AppState) {
+        let lines = if state.error_log.is_empty() {
+            vec!["No errors this session".to_string()]
+        } else {
+            state
+                .error_log
+                .iter()
+                .rev()
+                .map(|error| format!("[{}s ago] {}", error.at.elapsed().as_secs(), error.message))
+                .collect()
+        };
+        state.modal = Some(Modal::new(format!("Error Log ({})", state.error_log.len()), lines));
+    }
+
+    fn show_about(state: &mut AppState) {
+        let mut lines = crate::build_info::describe();
+        if let Some(version) = &state.available_update {
+            lines.push(String::new());
+            lines.push(format!("Update available: v{} (run `update`)", version));
+        }
+        state.modal = Some(Modal::new("About".to_string(), lines));
+    }
+
+    /// Open a modal with the selected pool's live throughput; the UI loop keeps it sampled
+    fn show_pool_iostat(state: &mut AppState) {
+        let AppView::PoolList = &state.current_view else {
+            return;
+        };
+        let Some(pool_name) = state.data_manager.pools.get(state.selected_pool_index).map(|p| p.name.clone()) else {
+            return;
+        };
+        state.modal = Some(Modal::iostat(pool_name));
+    }
+
+    /// Show `zfs diff` between the selected snapshot and the one created just before it
+    async fn show_snapshot_diff(state: &mut AppState) {
+        let AppView::SnapshotDetail(_, _) = &state.current_view else {
+            return;
+        };
+        let Some(selected) = state.data_manager.snapshots.get(state.selected_snapshot_index) else {
+            return;
+        };
+
+        // The list is recursive and may be sorted by anything, so search by creation time
+        // among snapshots of the same dataset
+        let dataset = selected.name.split('@').next().unwrap_or_default();
+        let Some(created) = selected.creation else {
+            state.set_info(format!("{} has no known creation time to compare against", selected.name));
+            return;
+        };
+        let previous = state
+            .data_manager
+            .snapshots
+            .iter()
+            .filter(|snapshot| snapshot.name.split('@').next() == Some(dataset))
+            .filter(|snapshot| snapshot.creation.is_some_and(|creation| creation < created))
+            .max_by_key(|snapshot| snapshot.creation);
+        let Some(previous) = previous else {
+            state.set_info(format!("{} is the oldest snapshot of {}", selected.name, dataset));
+            return;
+        };
+        let (older, newer) = (previous.name.clone(), selected.name.clone());
+
+        match crate::zfs::snapshot_diff(&state.data_manager.runner, &older, &newer).await {
+            Ok(diff) if diff.trim().is_empty() => state.set_info(format!("No changes between {} and {}", older, newer)),
+            Ok(diff) => {
+                let lines = diff.lines().map(|line| line.to_string()).collect();
+                state.modal = Some(Modal::new(format!("Diff: {} → {}", older, newer), lines));
+            }
+            Err(e) => state.set_error(format!("Failed to diff snapshots: {}", e)),
+        }
+    }
+
+    async fn copy_selected_name(state: &mut AppState) {
+        let name = match &state.current_view {
+            AppView::PoolList => state.data_manager.pools.get(state.selected_pool_index).map(|p| p.name.clone()),
+            AppView::DatasetView(_) => state.data_manager.datasets.get(state.selected_dataset_index).map(|d| d.name.clone()),
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => state.data_manager.snapshots.get(state.selected_snapshot_index).map(|s| s.name.clone()),
+            AppView::Help => None,
+        };
+        let Some(name) = name else {
+            return;
+        };
+
+        match crate::clipboard::copy_to_clipboard(&name).await {
+            Ok(()) => state.set_info(format!("Copied to clipboard: {}", name)),
+            // Headless or remote sessions have no clipboard, so show the name where it can be read
+            Err(_) => state.set_info(format!("Clipboard unavailable: {}", name)),
+        }
+    }
+
+    /// Move to the next sort order, or with `reverse` flip the direction of the current one.
+    /// The same item stays selected at its new position; the scroll offset only moves if
+    /// that position is off-screen.
+    fn toggle_sort(state: &mut AppState, reverse: bool) {
         match &state.current_view {
+            AppView::PoolList => {
+                let selected = state.data_manager.pools.get(state.selected_pool_index).map(|p| p.name.clone());
+                if reverse {
+                    state.sort_manager.reverse_pool_sort();
+                } else {
+                    state.sort_manager.toggle_pool_sort();
+                }
+                state.sort_manager.sort_pools(&mut state.data_manager.pools);
+                state.selected_pool_index = selected
+                    .and_then(|name| state.data_manager.pools.iter().position(|p| p.name == name))
+                    .unwrap_or(0);
+            }
             AppView::DatasetView(_) => {
-                state.sort_manager.toggle_dataset_sort();
-                state.sort_manager.sort_datasets(&mut state.data_manager.datasets);
-                state.reset_dataset_selection();
+                if reverse {
+                    state.sort_manager.reverse_dataset_sort();
+                } else {
+                    state.sort_manager.toggle_dataset_sort();
+                }
+                Self::rearrange_keeping_selection(state);
             }
-            AppView::SnapshotDetail(_, _) => {
-                state.sort_manager.toggle_snapshot_sort();
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => {
+                let selected = state.data_manager.snapshots.get(state.selected_snapshot_index).map(|s| s.name.clone());
+                if reverse {
+                    state.sort_manager.reverse_snapshot_sort();
+                } else {
+                    state.sort_manager.toggle_snapshot_sort();
+                }
                 state.sort_manager.sort_snapshots(&mut state.data_manager.snapshots);
-                state.reset_snapshot_selection();
+                state.selected_snapshot_index = selected
+                    .and_then(|name| state.data_manager.snapshots.iter().position(|s| s.name == name))
+                    .unwrap_or(0);
             }
             _ => {}
         }
     }
 
+    /// Space marks snapshots for deletion, or folds the selected dataset's subtree in tree view
+    fn toggle_mark(state: &mut AppState) {
+        match &state.current_view {
+            AppView::SnapshotDetail(_, _) if !state.config.readonly => state.toggle_snapshot_mark(),
+            AppView::DatasetView(_) if state.tree_view => Self::toggle_collapsed(state),
+            _ => {}
+        }
+    }
+
+    fn toggle_tree_view(state: &mut AppState) {
+        let AppView::DatasetView(_) = &state.current_view else {
+            return;
+        };
+        state.tree_view = !state.tree_view;
+        Self::rearrange_keeping_selection(state);
+    }
+
+    fn toggle_hide_inactive(state: &mut AppState) {
+        let AppView::DatasetView(_) = &state.current_view else {
+            return;
+        };
+        state.hide_inactive = !state.hide_inactive;
+        Self::rearrange_keeping_selection(state);
+    }
+
+    fn toggle_collapsed(state: &mut AppState) {
+        let Some(name) = state.data_manager.datasets.get(state.selected_dataset_index).map(|d| d.name.clone()) else {
+            return;
+        };
+        if !crate::dataset_tree::has_children(&state.data_manager.pool_datasets, &name) {
+            return;
+        }
+        if !state.collapsed_datasets.remove(&name) {
+            state.collapsed_datasets.insert(name);
+        }
+        Self::rearrange_keeping_selection(state);
+    }
+
+    fn rearrange_keeping_selection(state: &mut AppState) {
+        let selected = state.data_manager.datasets.get(state.selected_dataset_index).map(|d| d.name.clone());
+        state.arrange_datasets();
+        state.selected_dataset_index = selected
+            .and_then(|name| state.data_manager.datasets.iter().position(|d| d.name == name))
+            .unwrap_or(0);
+    }
+
     async fn handle_delete_key(state: &mut AppState) -> Result<()> {
         // Only allow deletion in snapshot view
         let AppView::SnapshotDetail(_pool_name, dataset_name) = &state.current_view else {
@@ -193,20 +1085,49 @@ impl Navigator {
         }
 
         if !state.delete_confirmation_pending {
-            // First 'd' press - start confirmation
-            state.start_delete_confirmation();
+            // First 'd' press - refuse snapshots that clones depend on, otherwise start confirmation
+            match Self::find_clone_dependents(state).await {
+                Ok(None) => {}
+                Ok(Some(message)) => {
+                    state.set_error(message);
+                    return Ok(());
+                }
+                Err(e) => {
+                    state.set_error(format!("Could not check for dependent clones: {:#}", e));
+                    return Ok(());
+                }
+            }
+            let targets = Self::delete_targets(state).into_iter().map(str::to_owned).collect();
+            state.start_delete_confirmation(targets);
+            return Ok(());
+        }
+
+        // Second 'd' press - only if the selection is still what the first press armed
+        if Self::delete_targets(state) != state.delete_confirmation_targets {
+            state.clear_delete_confirmation();
+            state.set_info("Selection changed; delete cancelled".to_string());
+            return Ok(());
+        }
+
+        // Execute deletion
+        if !state.selected_snapshots.is_empty() {
+            let dataset_name = dataset_name.clone();
+            Self::delete_marked_snapshots(state, &dataset_name).await?;
+            state.clear_delete_confirmation();
             return Ok(());
         }
 
-        // Second 'd' press - execute deletion
         let Some(snapshot) = state.data_manager.snapshots.get(state.selected_snapshot_index) else {
             state.clear_delete_confirmation();
             return Ok(());
         };
-        match crate::zfs::delete_snapshot(&snapshot.name).await {
+        match crate::zfs::delete_snapshot(&state.data_manager.runner, &snapshot.name, state.config.dry_run).await {
+            Ok(()) if state.config.dry_run => state.set_info(format!("Dry run: would delete {}", snapshot.name)),
             Ok(()) => {
                 // Force reload snapshots from ZFS after deletion
-                state.data_manager.reload_snapshots(dataset_name).await?;
+                if let Err(e) = state.data_manager.reload_snapshots(dataset_name).await {
+                    state.set_error(format!("Failed to reload snapshots: {:#}", e));
+                }
                 state.sort_manager.sort_snapshots(&mut state.data_manager.snapshots);
 
                 // Adjust selection if we deleted the last item
@@ -215,21 +1136,206 @@ impl Navigator {
                 }
             }
             Err(e) => {
-                // Extract a user-friendly error message
-                let error_msg = if e.to_string().contains("permission denied") {
-                    "Permission denied. Try running with elevated privileges (sudo).".to_string()
-                } else if e.to_string().contains("dataset does not exist") {
-                    "Snapshot no longer exists.".to_string()
-                } else if e.to_string().contains("dataset is busy") {
-                    "Snapshot is currently in use and cannot be deleted.".to_string()
-                } else {
-                    format!("Failed to delete snapshot: {}", e)
-                };
-                state.set_error(error_msg);
+                state.set_error(Self::describe_delete_error(&e));
             }
         }
 
         state.clear_delete_confirmation();
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Snapshots the delete key acts on: the marked ones in list order, or else the selected one
+    fn delete_targets(state: &AppState) -> Vec<&str> {
+        if state.selected_snapshots.is_empty() {
+            state
+                .data_manager
+                .snapshots
+                .get(state.selected_snapshot_index)
+                .map(|s| s.name.as_str())
+                .into_iter()
+                .collect()
+        } else {
+            state
+                .data_manager
+                .snapshots
+                .iter()
+                .filter(|s| state.selected_snapshots.contains(&s.name))
+                .map(|s| s.name.as_str())
+                .collect()
+        }
+    }
+
+    /// Copy the `zfs destroy` commands a pending delete will run, one per line, so they can be
+    /// recorded before confirming. Without a clipboard they are shown in a modal instead.
+    async fn copy_pending_commands(state: &mut AppState) {
+        let commands: Vec<String> = Self::delete_targets(state)
+            .into_iter()
+            .map(crate::zfs::delete_snapshot_command)
+            .collect();
+        if commands.is_empty() {
+            return;
+        }
+
+        match crate::clipboard::copy_to_clipboard(&commands.join("\n")).await {
+            Ok(()) if commands.len() == 1 => state.set_info(format!("Copied to clipboard: {}", commands[0])),
+            Ok(()) => state.set_info(format!("Copied {} commands to clipboard", commands.len())),
+            Err(_) => state.modal = Some(Modal::new("Pending commands (clipboard unavailable)".to_string(), commands)),
+        }
+    }
+
+    /// Describe the first targeted snapshot that has dependent clones, if any
+    async fn find_clone_dependents(state: &AppState) -> Result<Option<String>> {
+        let targets = Self::delete_targets(state);
+        let dependents = crate::zfs::snapshot_dependents(&state.data_manager.runner, &targets).await?;
+        Ok(targets.iter().find_map(|name| {
+            let clones: Vec<&str> = dependents
+                .iter()
+                .filter(|(_, origin)| origin == name)
+                .map(|(clone, _)| clone.as_str())
+                .collect();
+            (!clones.is_empty()).then(|| format!("Cannot delete {}: clones depend on it ({})", name, clones.join(", ")))
+        }))
+    }
+
+    async fn delete_marked_snapshots(state: &mut AppState, dataset_name: &str) -> Result<()> {
+        let names: Vec<String> = state
+            .data_manager
+            .snapshots
+            .iter()
+            .filter(|s| state.selected_snapshots.contains(&s.name))
+            .map(|s| s.name.clone())
+            .collect();
+
+        if state.config.dry_run {
+            crate::zfs::delete_snapshots(&state.data_manager.runner, &names, true).await;
+            state.selected_snapshots.clear();
+            state.set_info(format!("Dry run: would delete {} snapshots", names.len()));
+            return Ok(());
+        }
+
+        let results = crate::zfs::delete_snapshots(&state.data_manager.runner, &names, false).await;
+        // Each snapshot's `used` is what deleting it alone frees, so this is a lower bound
+        let freed: u64 = names
+            .iter()
+            .zip(&results)
+            .filter(|(_, result)| result.is_ok())
+            .filter_map(|(name, _)| state.data_manager.snapshots.iter().find(|s| &s.name == name))
+            .map(|s| s.used)
+            .sum();
+        let failures: Vec<String> = results
+            .into_iter()
+            .filter_map(|result| result.err())
+            .map(|e| Self::describe_delete_error(&e))
+            .collect();
+        let succeeded = names.len() - failures.len();
+
+        state.selected_snapshots.clear();
+        if let Err(e) = state.data_manager.reload_snapshots(dataset_name).await {
+            state.set_error(format!("Failed to reload snapshots: {:#}", e));
+        }
+        state.sort_manager.sort_snapshots(&mut state.data_manager.snapshots);
+        if state.selected_snapshot_index >= state.data_manager.snapshots.len() {
+            state.selected_snapshot_index = state.data_manager.snapshots.len().saturating_sub(1);
+        }
+
+        if let Some(first_failure) = failures.first() {
+            state.set_error(format!(
+                "Deleted {} of {} snapshots, {} failed: {}",
+                succeeded,
+                names.len(),
+                failures.len(),
+                first_failure
+            ));
+        } else {
+            state.set_info(format!(
+                "Deleted {} snapshots, freeing at least {}",
+                succeeded,
+                crate::zfs::format_bytes(freed, state.config.units)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Extract a user-friendly error message from a failed `zfs destroy`
+    fn describe_delete_error(e: &anyhow::Error) -> String {
+        if e.to_string().contains("permission denied") {
+            "Permission denied. Try running with elevated privileges (sudo).".to_string()
+        } else if e.to_string().contains("dataset does not exist") {
+            "Snapshot no longer exists.".to_string()
+        } else if e.to_string().contains("dataset is busy") {
+            "Snapshot is currently in use and cannot be deleted.".to_string()
+        } else {
+            format!("Failed to delete snapshot: {}", e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::zfs::Dataset;
+
+    fn dataset_view(names: &[&str]) -> AppState {
+        let mut state = AppState::new(Config::default());
+        state.data_manager.datasets = names
+            .iter()
+            .map(|name| Dataset { name: name.to_string(), ..Default::default() })
+            .collect();
+        state.current_view = AppView::DatasetView("tank".to_string());
+        state
+    }
+
+    async fn press(state: &mut AppState, keys: &str) {
+        for c in keys.chars() {
+            Navigator::handle_key_event(state, KeyCode::Char(c), KeyModifiers::NONE).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn slash_jumps_to_names_starting_with_command_keys() {
+        let mut state = dataset_view(&["tank", "tank/data", "tank/home", "tank/scratch"]);
+
+        // Without '/', 'h' opens help and 's' changes the sort; after it they spell names
+        press(&mut state, "/ho").await;
+        assert!(matches!(&state.current_view, AppView::DatasetView(pool) if pool == "tank"));
+        assert_eq!(state.selected_dataset_index, 2);
+
+        press(&mut state, "/sc").await;
+        assert_eq!(state.selected_dataset_index, 3);
+        assert_eq!(state.sort_manager.dataset_sort_order, crate::sorting::DatasetSortOrder::TotalSizeDesc);
+
+        press(&mut state, "/da").await;
+        assert_eq!(state.selected_dataset_index, 1);
+    }
+    #[tokio::test]
+    async fn snapshot_details_list_clones_from_the_pool_listing() {
+        let mut state = AppState::new(Config::default());
+        state.data_manager.pool_datasets = vec![
+            Dataset { name: "tank/home".to_string(), ..Default::default() },
+            Dataset { name: "tank/clone".to_string(), origin: Some("tank/home@daily".to_string()), ..Default::default() },
+            Dataset { name: "tank/other".to_string(), origin: Some("tank/home@weekly".to_string()), ..Default::default() },
+        ];
+        state.data_manager.snapshots = vec![crate::zfs::Snapshot { name: "tank/home@daily".to_string(), ..Default::default() }];
+        state.current_view = AppView::SnapshotDetail("tank".to_string(), "tank/home".to_string());
+
+        Navigator::show_details(&mut state).await;
+
+        let modal = state.modal.expect("details modal");
+        assert!(modal.lines.iter().any(|line| line.ends_with("tank/clone")));
+        assert!(!modal.lines.iter().any(|line| line.contains("tank/other")));
+    }
+    #[test]
+    fn prune_leaves_snapshots_without_a_creation_time_unmarked() {
+        let mut state = AppState::new(Config::default());
+        state.data_manager.snapshots = vec![
+            crate::zfs::Snapshot { name: "tank/home@old".to_string(), creation: Some(1_000), ..Default::default() },
+            crate::zfs::Snapshot { name: "tank/home@unknown".to_string(), creation: None, ..Default::default() },
+        ];
+
+        Navigator::mark_snapshots_older_than(&mut state, 1);
+
+        assert_eq!(state.selected_snapshots.len(), 1);
+        assert!(state.selected_snapshots.contains("tank/home@old"));
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+
+use crate::config::ByteUnits;
+use crate::zfs::{format_bytes, Pool, TokioCommandRunner};
+
+/// Exit code when a pool is at or above the alarm threshold, the monitoring plugin convention
+/// for a check that needs attention
+const ALARM_EXIT_CODE: i32 = 2;
+
+/// Print one line per pool and return the process exit code: 0, or `ALARM_EXIT_CODE` when any
+/// pool's capacity reaches `alarm_threshold` percent
+pub async fn run(runner: &TokioCommandRunner, units: ByteUnits, alarm_threshold: Option<u8>) -> Result<i32> {
+    let pools = crate::zfs::get_pools(runner).await?.rows;
+
+    for pool in &pools {
+        println!(
+            "{} {} {}/{} {}%",
+            pool.name,
+            pool.health,
+            format_bytes(pool.allocated, units),
+            format_bytes(pool.size, units),
+            pool.capacity
+        );
+    }
+
+    let Some(threshold) = alarm_threshold else {
+        return Ok(0);
+    };
+    let tripped: Vec<&Pool> = pools.iter().filter(|pool| pool.capacity >= u64::from(threshold)).collect();
+    for pool in &tripped {
+        println!("ALARM: {} is {}% full (threshold {}%)", pool.name, pool.capacity, threshold);
+    }
+    Ok(if tripped.is_empty() { 0 } else { ALARM_EXIT_CODE })
+}
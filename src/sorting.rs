@@ -1,7 +1,70 @@
-use crate::zfs::{Dataset, Snapshot};
+use clap::ValueEnum;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+use crate::zfs::{Dataset, Pool, Snapshot};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PoolSortOrder {
+    #[default]
+    NameAsc,
+    NameDesc,
+    AllocatedDesc,
+    AllocatedAsc,
+    FreeDesc,
+    FreeAsc,
+    UsageDesc,
+    UsageAsc,
+}
+
+impl PoolSortOrder {
+    const VALUES: [Self; 8] = [
+        Self::NameAsc, Self::NameDesc, Self::AllocatedDesc, Self::AllocatedAsc,
+        Self::FreeDesc, Self::FreeAsc, Self::UsageDesc, Self::UsageAsc,
+    ];
+
+    pub const fn next(self) -> Self {
+        let current_idx = match self {
+            Self::NameAsc => 0,
+            Self::NameDesc => 1,
+            Self::AllocatedDesc => 2,
+            Self::AllocatedAsc => 3,
+            Self::FreeDesc => 4,
+            Self::FreeAsc => 5,
+            Self::UsageDesc => 6,
+            Self::UsageAsc => 7,
+        };
+        Self::VALUES[(current_idx + 1) % Self::VALUES.len()]
+    }
+
+    /// The same sort key in the opposite direction
+    pub const fn reversed(self) -> Self {
+        match self {
+            Self::NameAsc => Self::NameDesc,
+            Self::NameDesc => Self::NameAsc,
+            Self::AllocatedDesc => Self::AllocatedAsc,
+            Self::AllocatedAsc => Self::AllocatedDesc,
+            Self::FreeDesc => Self::FreeAsc,
+            Self::FreeAsc => Self::FreeDesc,
+            Self::UsageDesc => Self::UsageAsc,
+            Self::UsageAsc => Self::UsageDesc,
+        }
+    }
+}
+
+fn pool_free(pool: &Pool) -> u64 {
+    pool.size.saturating_sub(pool.allocated)
+}
+
+fn pool_usage(pool: &Pool) -> f64 {
+    if pool.size > 0 {
+        pool.allocated as f64 / pool.size as f64
+    } else {
+        0.0
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
 pub enum DatasetSortOrder {
+    #[default]
     TotalSizeDesc,
     TotalSizeAsc,
     DatasetSizeDesc,
@@ -31,28 +94,39 @@ impl DatasetSortOrder {
         };
         Self::VALUES[(current_idx + 1) % Self::VALUES.len()]
     }
-}
 
-impl Default for DatasetSortOrder {
-    fn default() -> Self {
-        Self::TotalSizeDesc
+    /// The same sort key in the opposite direction
+    pub const fn reversed(self) -> Self {
+        match self {
+            Self::TotalSizeDesc => Self::TotalSizeAsc,
+            Self::TotalSizeAsc => Self::TotalSizeDesc,
+            Self::DatasetSizeDesc => Self::DatasetSizeAsc,
+            Self::DatasetSizeAsc => Self::DatasetSizeDesc,
+            Self::SnapshotSizeDesc => Self::SnapshotSizeAsc,
+            Self::SnapshotSizeAsc => Self::SnapshotSizeDesc,
+            Self::NameDesc => Self::NameAsc,
+            Self::NameAsc => Self::NameDesc,
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
 pub enum SnapshotSortOrder {
+    #[default]
     UsedDesc,
     UsedAsc,
     ReferencedDesc,
     ReferencedAsc,
+    WrittenDesc,
+    WrittenAsc,
     NameDesc,
     NameAsc,
 }
 
 impl SnapshotSortOrder {
-    const VALUES: [Self; 6] = [
-        Self::UsedDesc, Self::UsedAsc, Self::ReferencedDesc,
-        Self::ReferencedAsc, Self::NameDesc, Self::NameAsc,
+    const VALUES: [Self; 8] = [
+        Self::UsedDesc, Self::UsedAsc, Self::ReferencedDesc, Self::ReferencedAsc,
+        Self::WrittenDesc, Self::WrittenAsc, Self::NameDesc, Self::NameAsc,
     ];
 
     pub const fn next(self) -> Self {
@@ -61,55 +135,94 @@ impl SnapshotSortOrder {
             Self::UsedAsc => 1,
             Self::ReferencedDesc => 2,
             Self::ReferencedAsc => 3,
-            Self::NameDesc => 4,
-            Self::NameAsc => 5,
+            Self::WrittenDesc => 4,
+            Self::WrittenAsc => 5,
+            Self::NameDesc => 6,
+            Self::NameAsc => 7,
         };
         Self::VALUES[(current_idx + 1) % Self::VALUES.len()]
     }
-}
 
-impl Default for SnapshotSortOrder {
-    fn default() -> Self {
-        Self::UsedDesc
+    /// The same sort key in the opposite direction
+    pub const fn reversed(self) -> Self {
+        match self {
+            Self::UsedDesc => Self::UsedAsc,
+            Self::UsedAsc => Self::UsedDesc,
+            Self::ReferencedDesc => Self::ReferencedAsc,
+            Self::ReferencedAsc => Self::ReferencedDesc,
+            Self::WrittenDesc => Self::WrittenAsc,
+            Self::WrittenAsc => Self::WrittenDesc,
+            Self::NameDesc => Self::NameAsc,
+            Self::NameAsc => Self::NameDesc,
+        }
     }
 }
 
 #[derive(Default)]
 pub struct SortManager {
+    pub pool_sort_order: PoolSortOrder,
     pub dataset_sort_order: DatasetSortOrder,
     pub snapshot_sort_order: SnapshotSortOrder,
 }
 
 
 impl SortManager {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(dataset_sort_order: DatasetSortOrder, snapshot_sort_order: SnapshotSortOrder) -> Self {
+        Self {
+            dataset_sort_order,
+            snapshot_sort_order,
+            ..Self::default()
+        }
+    }
+
+    pub fn sort_pools(&self, pools: &mut [Pool]) {
+        // Ties on the primary key fall back to ascending name so the order is deterministic
+        let by_name = |a: &Pool, b: &Pool| a.name.cmp(&b.name);
+        match self.pool_sort_order {
+            PoolSortOrder::NameAsc => pools.sort_by(by_name),
+            PoolSortOrder::NameDesc => pools.sort_by(|a, b| b.name.cmp(&a.name)),
+            PoolSortOrder::AllocatedDesc => pools.sort_by(|a, b| b.allocated.cmp(&a.allocated).then_with(|| by_name(a, b))),
+            PoolSortOrder::AllocatedAsc => pools.sort_by(|a, b| a.allocated.cmp(&b.allocated).then_with(|| by_name(a, b))),
+            PoolSortOrder::FreeDesc => pools.sort_by(|a, b| pool_free(b).cmp(&pool_free(a)).then_with(|| by_name(a, b))),
+            PoolSortOrder::FreeAsc => pools.sort_by(|a, b| pool_free(a).cmp(&pool_free(b)).then_with(|| by_name(a, b))),
+            PoolSortOrder::UsageDesc => pools.sort_by(|a, b| pool_usage(b).total_cmp(&pool_usage(a)).then_with(|| by_name(a, b))),
+            PoolSortOrder::UsageAsc => pools.sort_by(|a, b| pool_usage(a).total_cmp(&pool_usage(b)).then_with(|| by_name(a, b))),
+        }
     }
 
     pub fn sort_datasets(&self, datasets: &mut [Dataset]) {
+        let by_name = |a: &Dataset, b: &Dataset| a.name.cmp(&b.name);
+        let total = |d: &Dataset| d.referenced + d.snapshot_bytes();
         match self.dataset_sort_order {
-            DatasetSortOrder::TotalSizeDesc => datasets.sort_by(|a, b| (b.referenced + b.snapshot_used).cmp(&(a.referenced + a.snapshot_used))),
-            DatasetSortOrder::TotalSizeAsc => datasets.sort_by(|a, b| (a.referenced + a.snapshot_used).cmp(&(b.referenced + b.snapshot_used))),
-            DatasetSortOrder::DatasetSizeDesc => datasets.sort_by(|a, b| b.referenced.cmp(&a.referenced)),
-            DatasetSortOrder::DatasetSizeAsc => datasets.sort_by(|a, b| a.referenced.cmp(&b.referenced)),
-            DatasetSortOrder::SnapshotSizeDesc => datasets.sort_by(|a, b| b.snapshot_used.cmp(&a.snapshot_used)),
-            DatasetSortOrder::SnapshotSizeAsc => datasets.sort_by(|a, b| a.snapshot_used.cmp(&b.snapshot_used)),
+            DatasetSortOrder::TotalSizeDesc => datasets.sort_by(|a, b| total(b).cmp(&total(a)).then_with(|| by_name(a, b))),
+            DatasetSortOrder::TotalSizeAsc => datasets.sort_by(|a, b| total(a).cmp(&total(b)).then_with(|| by_name(a, b))),
+            DatasetSortOrder::DatasetSizeDesc => datasets.sort_by(|a, b| b.referenced.cmp(&a.referenced).then_with(|| by_name(a, b))),
+            DatasetSortOrder::DatasetSizeAsc => datasets.sort_by(|a, b| a.referenced.cmp(&b.referenced).then_with(|| by_name(a, b))),
+            DatasetSortOrder::SnapshotSizeDesc => datasets.sort_by(|a, b| b.snapshot_bytes().cmp(&a.snapshot_bytes()).then_with(|| by_name(a, b))),
+            DatasetSortOrder::SnapshotSizeAsc => datasets.sort_by(|a, b| a.snapshot_bytes().cmp(&b.snapshot_bytes()).then_with(|| by_name(a, b))),
             DatasetSortOrder::NameDesc => datasets.sort_by(|a, b| b.name.cmp(&a.name)),
-            DatasetSortOrder::NameAsc => datasets.sort_by(|a, b| a.name.cmp(&b.name)),
+            DatasetSortOrder::NameAsc => datasets.sort_by(by_name),
         }
     }
 
     pub fn sort_snapshots(&self, snapshots: &mut [Snapshot]) {
+        let by_name = |a: &Snapshot, b: &Snapshot| a.name.cmp(&b.name);
         match self.snapshot_sort_order {
-            SnapshotSortOrder::UsedDesc => snapshots.sort_by(|a, b| b.used.cmp(&a.used)),
-            SnapshotSortOrder::UsedAsc => snapshots.sort_by(|a, b| a.used.cmp(&b.used)),
-            SnapshotSortOrder::ReferencedDesc => snapshots.sort_by(|a, b| b.referenced.cmp(&a.referenced)),
-            SnapshotSortOrder::ReferencedAsc => snapshots.sort_by(|a, b| a.referenced.cmp(&b.referenced)),
+            SnapshotSortOrder::UsedDesc => snapshots.sort_by(|a, b| b.used.cmp(&a.used).then_with(|| by_name(a, b))),
+            SnapshotSortOrder::UsedAsc => snapshots.sort_by(|a, b| a.used.cmp(&b.used).then_with(|| by_name(a, b))),
+            SnapshotSortOrder::ReferencedDesc => snapshots.sort_by(|a, b| b.referenced.cmp(&a.referenced).then_with(|| by_name(a, b))),
+            SnapshotSortOrder::ReferencedAsc => snapshots.sort_by(|a, b| a.referenced.cmp(&b.referenced).then_with(|| by_name(a, b))),
+            SnapshotSortOrder::WrittenDesc => snapshots.sort_by(|a, b| b.written.cmp(&a.written).then_with(|| by_name(a, b))),
+            SnapshotSortOrder::WrittenAsc => snapshots.sort_by(|a, b| a.written.cmp(&b.written).then_with(|| by_name(a, b))),
             SnapshotSortOrder::NameDesc => snapshots.sort_by(|a, b| b.name.cmp(&a.name)),
-            SnapshotSortOrder::NameAsc => snapshots.sort_by(|a, b| a.name.cmp(&b.name)),
+            SnapshotSortOrder::NameAsc => snapshots.sort_by(by_name),
         }
     }
 
+    pub fn toggle_pool_sort(&mut self) {
+        self.pool_sort_order = self.pool_sort_order.next();
+    }
+
     pub fn toggle_dataset_sort(&mut self) {
         self.dataset_sort_order = self.dataset_sort_order.next();
     }
@@ -118,6 +231,31 @@ impl SortManager {
         self.snapshot_sort_order = self.snapshot_sort_order.next();
     }
 
+    pub fn reverse_pool_sort(&mut self) {
+        self.pool_sort_order = self.pool_sort_order.reversed();
+    }
+
+    pub fn reverse_dataset_sort(&mut self) {
+        self.dataset_sort_order = self.dataset_sort_order.reversed();
+    }
+
+    pub fn reverse_snapshot_sort(&mut self) {
+        self.snapshot_sort_order = self.snapshot_sort_order.reversed();
+    }
+
+    pub fn get_pool_sort_indicator(&self) -> &'static str {
+        match self.pool_sort_order {
+            PoolSortOrder::NameAsc => "Name ↑",
+            PoolSortOrder::NameDesc => "Name ↓",
+            PoolSortOrder::AllocatedDesc => "Allocated ↓",
+            PoolSortOrder::AllocatedAsc => "Allocated ↑",
+            PoolSortOrder::FreeDesc => "Free ↓",
+            PoolSortOrder::FreeAsc => "Free ↑",
+            PoolSortOrder::UsageDesc => "Usage % ↓",
+            PoolSortOrder::UsageAsc => "Usage % ↑",
+        }
+    }
+
     pub fn get_dataset_sort_indicator(&self) -> &'static str {
         match self.dataset_sort_order {
             DatasetSortOrder::TotalSizeDesc => "Total Size ↓",
@@ -137,6 +275,8 @@ impl SortManager {
             SnapshotSortOrder::UsedAsc => "Used Size ↑",
             SnapshotSortOrder::ReferencedDesc => "Referenced Size ↓",
             SnapshotSortOrder::ReferencedAsc => "Referenced Size ↑",
+            SnapshotSortOrder::WrittenDesc => "Written ↓",
+            SnapshotSortOrder::WrittenAsc => "Written ↑",
             SnapshotSortOrder::NameDesc => "Name ↓",
             SnapshotSortOrder::NameAsc => "Name ↑",
         }
@@ -3,21 +3,113 @@ use crate::{
     sorting::SortManager,
     theme::ThemeManager,
     config::Config,
+    keymap::{Action, Keymap},
 };
-use std::time::Instant;
+use std::{collections::{HashMap, HashSet, VecDeque}, time::Instant};
 
 #[derive(Debug, Clone)]
 pub enum AppView {
     PoolList,
     DatasetView(String), // pool name
     SnapshotDetail(String, String), // pool name, dataset name
+    AllSnapshots, // every cached snapshot across pools
     Help,
 }
 
+/// How many errors the error log keeps before dropping the oldest
+const ERROR_LOG_LIMIT: usize = 100;
+
+pub struct LoggedError {
+    pub at: Instant,
+    pub message: String,
+}
+
+/// A scrollable text popup drawn over the current view
+pub struct Modal {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub scroll_offset: usize,
+    // Pool whose live I/O this modal shows; samples are only taken while it is open
+    pub iostat_pool: Option<String>,
+}
+
+impl Modal {
+    pub fn new(title: String, lines: Vec<String>) -> Self {
+        Self {
+            title,
+            lines,
+            scroll_offset: 0,
+            iostat_pool: None,
+        }
+    }
+
+    /// A modal kept updated with the pool's throughput by the UI loop
+    pub fn iostat(pool_name: String) -> Self {
+        Self {
+            iostat_pool: Some(pool_name.clone()),
+            ..Self::new(format!("Live I/O: {}", pool_name), vec!["Sampling...".to_string()])
+        }
+    }
+}
+
+/// What to do with the text once a prompt is submitted
+#[derive(Debug, Clone)]
+pub enum InputAction {
+    /// Create `<parent>/<text>`
+    CreateDataset { parent: String },
+    /// Destroy the dataset once its full name is typed back; `recursive` also takes its
+    /// children and snapshots and needs a second, separate confirmation
+    DestroyDataset { name: String, recursive: bool },
+    /// Rename the snapshot, keeping its dataset; the text is the new part after `@`
+    RenameSnapshot { old: String },
+    /// Run the text through the shell with the TUI suspended
+    ShellCommand,
+    /// Mark the open dataset's snapshots created more than the typed number of days ago
+    PruneSnapshots,
+}
+
+/// Selected row of a list and how far it was scrolled, restored when the list is reopened
+pub struct ListPosition {
+    // Kept by name because the row's index can change when the list is reloaded
+    pub selected: String,
+    pub scroll_offset: usize,
+}
+
+/// A single-line text prompt drawn over the current view
+pub struct TextInput {
+    pub title: String,
+    // Fixed text shown before the editable part, e.g. the parent dataset
+    pub prefix: String,
+    pub value: String,
+    pub action: InputAction,
+}
+
+impl TextInput {
+    pub fn new(title: String, prefix: String, action: InputAction) -> Self {
+        Self {
+            title,
+            prefix,
+            value: String::new(),
+            action,
+        }
+    }
+
+    pub fn with_value(mut self, value: String) -> Self {
+        self.value = value;
+        self
+    }
+}
+
 pub struct AppState {
     pub should_quit: bool,
+    // Incremented once per event loop iteration to drive animations
+    pub tick: usize,
     pub current_view: AppView,
     pub previous_view: Option<AppView>,
+    pub modal: Option<Modal>,
+    pub input: Option<TextInput>,
+    // Shell command waiting for the event loop to suspend the terminal and run it
+    pub pending_shell_command: Option<String>,
 
     // Selection indices
     pub selected_pool_index: usize,
@@ -27,21 +119,74 @@ pub struct AppState {
     // Scroll offsets
     pub dataset_scroll_offset: usize,
     pub snapshot_scroll_offset: usize,
+    pub help_scroll_offset: usize,
+    // Positions left behind in each pool's dataset list and each dataset's snapshot list
+    pub dataset_positions: HashMap<String, ListPosition>,
+    pub snapshot_positions: HashMap<String, ListPosition>,
+
+    // Screen area of the current list, recorded at draw time for mouse hit-testing
+    pub list_area: ratatui::layout::Rect,
 
     // Component managers
     pub data_manager: DataManager,
     pub sort_manager: SortManager,
     pub theme_manager: ThemeManager,
+    pub keymap: Keymap,
 
     // Configuration
     pub config: Config,
 
+    // Show the usedby* breakdown instead of the D/S/T bars in the dataset view
+    pub show_usage_breakdown: bool,
+
+    // Show pool usage against usable (post-redundancy) capacity instead of raw zpool size
+    pub show_usable_capacity: bool,
+
+    // Scale dataset bars against the pool's usable size instead of the largest dataset
+    pub scale_to_pool: bool,
+
+    // Show datasets nested under their parents, with these subtrees folded into their parent
+    pub tree_view: bool,
+    pub collapsed_datasets: HashSet<String>,
+
+    // Leave out datasets with canmount=off or no space used
+    pub hide_inactive: bool,
+
+    // Snapshots marked for batch deletion, by full name
+    pub selected_snapshots: HashSet<String>,
+
+    // Pending first key of a multi-key sequence (e.g. the first 'g' of "gg")
+    pub pending_key: Option<char>,
+
+    // Type-ahead buffer for jumping to a dataset by name prefix, and when it was last extended
+    pub type_ahead: String,
+    pub type_ahead_timestamp: Option<Instant>,
+
     // Deletion confirmation state
     pub delete_confirmation_pending: bool,
     pub delete_confirmation_timestamp: Option<Instant>,
+    // Snapshots the first 'd' armed; the second 'd' only deletes if they are still the targets
+    pub delete_confirmation_targets: Vec<String>,
+    // Zero means the confirmation never expires
+    pub delete_confirmation_timeout_secs: u64,
+
+    // Set by the first 'q' with --confirm-quit; shares the delete confirmation timeout
+    pub quit_confirmation_timestamp: Option<Instant>,
 
     // Error state
     pub error_message: Option<String>,
+    // Recent errors, oldest first, including background failures that never reach error_message
+    pub error_log: VecDeque<LoggedError>,
+
+    // Transient informational message, cleared on the next key press
+    pub info_message: Option<String>,
+
+    // Sample being taken for the live I/O modal, with the pool it is for
+    pub iostat_task: Option<(String, tokio::task::JoinHandle<anyhow::Result<crate::zfs::PoolIostat>>)>,
+
+    // Background release check started with --check-updates, and the newer version it found
+    pub update_check: Option<tokio::task::JoinHandle<Option<String>>>,
+    pub available_update: Option<String>,
 
     // Cached status text
     pub status_help_text: String,
@@ -52,29 +197,82 @@ impl AppState {
     pub fn new(config: Config) -> Self {
         let thread_count = config.effective_thread_count();
         let readonly = config.readonly;
+        let delete_confirmation_timeout_secs = config.confirm_timeout;
 
-        let status_help_text = if readonly {
-            "↑/↓: Navigate | PgUp/PgDn: Page | s: Sort | ←/Esc: Back | h: Help | q: Quit (READONLY MODE)".to_string()
-        } else {
-            "↑/↓: Navigate | PgUp/PgDn: Page | d: Delete | s: Sort | ←/Esc: Back | h: Help | q: Quit".to_string()
-        };
+        let mut theme_manager = ThemeManager::new();
+        let mut error_message = None;
+        if let Some(path) = &config.theme_file {
+            match crate::theme::load_theme_file(path) {
+                Ok(theme) => theme_manager.add_theme(theme),
+                Err(e) => error_message = Some(format!("Failed to load theme: {:#}", e)),
+            }
+        }
+
+        let mut keymap = Keymap::default();
+        if let Some(path) = &config.keymap_file {
+            match crate::keymap::load_keymap_file(path) {
+                Ok(loaded) => keymap = loaded,
+                Err(e) => error_message = Some(format!("Failed to load keymap: {:#}", e)),
+            }
+        }
+
+        let status_help_text = navigation_help_text(readonly, &keymap);
+
+        let mut error_log = VecDeque::new();
+        if let Some(message) = &error_message {
+            error_log.push_back(LoggedError { at: Instant::now(), message: message.clone() });
+        }
 
         Self {
             should_quit: false,
+            tick: 0,
             current_view: AppView::PoolList,
             previous_view: None,
+            modal: None,
+            input: None,
+            pending_shell_command: None,
             selected_pool_index: 0,
             selected_dataset_index: 0,
             selected_snapshot_index: 0,
             dataset_scroll_offset: 0,
             snapshot_scroll_offset: 0,
-            data_manager: DataManager::new(thread_count),
-            sort_manager: SortManager::new(),
-            theme_manager: ThemeManager::new(),
+            help_scroll_offset: 0,
+            dataset_positions: HashMap::new(),
+            snapshot_positions: HashMap::new(),
+            list_area: ratatui::layout::Rect::default(),
+            data_manager: DataManager::new(
+                crate::zfs::TokioCommandRunner::new(config.command_timeout),
+                thread_count,
+                config.cache_size,
+                !config.no_prefetch,
+                config.hide_patterns(),
+                config.min_size,
+            ),
+            sort_manager: SortManager::new(config.sort_datasets, config.sort_snapshots),
+            theme_manager,
+            keymap,
             config,
+            show_usage_breakdown: false,
+            show_usable_capacity: false,
+            scale_to_pool: false,
+            tree_view: false,
+            hide_inactive: false,
+            collapsed_datasets: HashSet::new(),
+            selected_snapshots: HashSet::new(),
+            pending_key: None,
+            type_ahead: String::new(),
+            type_ahead_timestamp: None,
             delete_confirmation_pending: false,
             delete_confirmation_timestamp: None,
-            error_message: None,
+            delete_confirmation_targets: Vec::new(),
+            delete_confirmation_timeout_secs,
+            quit_confirmation_timestamp: None,
+            error_message,
+            error_log,
+            info_message: None,
+            iostat_task: None,
+            update_check: None,
+            available_update: None,
             status_help_text,
             status_help_color: ratatui::style::Color::Reset,
         }
@@ -83,7 +281,7 @@ impl AppState {
     pub fn get_visible_range(&self, total_items: usize, visible_height: usize) -> (usize, usize) {
         let scroll_offset = match &self.current_view {
             AppView::DatasetView(_) => self.dataset_scroll_offset,
-            AppView::SnapshotDetail(_, _) => self.snapshot_scroll_offset,
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => self.snapshot_scroll_offset,
             _ => 0,
         };
 
@@ -116,7 +314,7 @@ impl AppState {
                     self.dataset_scroll_offset = self.dataset_scroll_offset.min(max_scroll);
                 }
             }
-            AppView::SnapshotDetail(_, _) => {
+            AppView::SnapshotDetail(_, _) | AppView::AllSnapshots => {
                 let total_items = self.data_manager.snapshots.len();
                 if total_items <= visible_height {
                     // All items fit on screen, no scrolling needed
@@ -142,25 +340,78 @@ impl AppState {
         }
     }
 
+    /// Re-sort the dataset rows and, in tree view, nest them and fold collapsed subtrees
+    pub fn arrange_datasets(&mut self) {
+        let collapsed = self.tree_view.then_some(&self.collapsed_datasets);
+        self.data_manager.arrange_datasets(&self.sort_manager, collapsed, self.hide_inactive);
+    }
+
     pub fn reset_dataset_selection(&mut self) {
         self.selected_dataset_index = 0;
         self.dataset_scroll_offset = 0;
     }
 
+    pub fn remember_dataset_position(&mut self, pool_name: &str) {
+        if let Some(dataset) = self.data_manager.datasets.get(self.selected_dataset_index) {
+            let position = ListPosition { selected: dataset.name.clone(), scroll_offset: self.dataset_scroll_offset };
+            self.dataset_positions.insert(pool_name.to_string(), position);
+        }
+    }
+
+    /// Select the dataset that was selected when the pool was last left, if it is still listed
+    pub fn restore_dataset_position(&mut self, pool_name: &str) {
+        let Some(position) = self.dataset_positions.get(pool_name) else {
+            return;
+        };
+        if let Some(index) = self.data_manager.datasets.iter().position(|d| d.name == position.selected) {
+            self.selected_dataset_index = index;
+            self.dataset_scroll_offset = position.scroll_offset;
+        }
+    }
+
+    pub fn remember_snapshot_position(&mut self, dataset_name: &str) {
+        if let Some(snapshot) = self.data_manager.snapshots.get(self.selected_snapshot_index) {
+            let position = ListPosition { selected: snapshot.name.clone(), scroll_offset: self.snapshot_scroll_offset };
+            self.snapshot_positions.insert(dataset_name.to_string(), position);
+        }
+    }
+
+    /// Select the snapshot that was selected when the dataset was last left, if it still exists
+    pub fn restore_snapshot_position(&mut self, dataset_name: &str) {
+        let Some(position) = self.snapshot_positions.get(dataset_name) else {
+            return;
+        };
+        if let Some(index) = self.data_manager.snapshots.iter().position(|s| s.name == position.selected) {
+            self.selected_snapshot_index = index;
+            self.snapshot_scroll_offset = position.scroll_offset;
+        }
+    }
+
+    pub fn toggle_snapshot_mark(&mut self) {
+        let Some(snapshot) = self.data_manager.snapshots.get(self.selected_snapshot_index) else {
+            return;
+        };
+        if !self.selected_snapshots.remove(&snapshot.name) {
+            self.selected_snapshots.insert(snapshot.name.clone());
+        }
+    }
+
     pub fn reset_snapshot_selection(&mut self) {
         self.selected_snapshot_index = 0;
         self.snapshot_scroll_offset = 0;
     }
 
-    pub fn start_delete_confirmation(&mut self) {
+    pub fn start_delete_confirmation(&mut self, targets: Vec<String>) {
         self.delete_confirmation_pending = true;
         self.delete_confirmation_timestamp = Some(Instant::now());
+        self.delete_confirmation_targets = targets;
         self.update_status_help_text();
     }
 
     pub fn clear_delete_confirmation(&mut self) {
         self.delete_confirmation_pending = false;
         self.delete_confirmation_timestamp = None;
+        self.delete_confirmation_targets.clear();
         // Only update status help text if there's no error to preserve
         if self.error_message.is_none() {
             self.update_status_help_text();
@@ -168,23 +419,89 @@ impl AppState {
     }
 
     pub fn is_delete_confirmation_expired(&self) -> bool {
-        if let Some(timestamp) = self.delete_confirmation_timestamp {
-            timestamp.elapsed().as_secs() >= crate::navigation::DELETE_CONFIRMATION_TIMEOUT_SECS
+        self.is_confirmation_expired(self.delete_confirmation_timestamp)
+    }
+
+    pub fn is_quit_confirmation_expired(&self) -> bool {
+        self.is_confirmation_expired(self.quit_confirmation_timestamp)
+    }
+
+    fn is_confirmation_expired(&self, timestamp: Option<Instant>) -> bool {
+        if self.delete_confirmation_timeout_secs == 0 {
+            return false;
+        }
+        if let Some(timestamp) = timestamp {
+            timestamp.elapsed().as_secs() >= self.delete_confirmation_timeout_secs
         } else {
             false
         }
     }
 
+    /// Quit, or with --confirm-quit ask for a second 'q' unless this is that second press
+    pub fn request_quit(&mut self, confirmed: bool) {
+        if !self.config.confirm_quit || confirmed {
+            self.should_quit = true;
+            return;
+        }
+        self.quit_confirmation_timestamp = Some(Instant::now());
+        self.set_info(format!("Press q again{} to quit", self.delete_confirmation_window()));
+    }
+
+    /// Describe how long the user has to confirm, e.g. " within 3s"
+    pub fn delete_confirmation_window(&self) -> String {
+        if self.delete_confirmation_timeout_secs == 0 {
+            String::new()
+        } else {
+            format!(" within {}s", self.delete_confirmation_timeout_secs)
+        }
+    }
+
+    /// Space the pending delete frees, e.g. " (frees 1.2GiB)". Snapshots can share blocks, so
+    /// for several marked snapshots the sum of their `used` is only a lower bound.
+    pub fn delete_space_summary(&self) -> String {
+        let units = self.config.units;
+        if self.selected_snapshots.is_empty() {
+            self.data_manager
+                .snapshots
+                .get(self.selected_snapshot_index)
+                .map(|s| format!(" (frees {})", crate::zfs::format_bytes(s.used, units)))
+                .unwrap_or_default()
+        } else {
+            let used: u64 = self
+                .data_manager
+                .snapshots
+                .iter()
+                .filter(|s| self.selected_snapshots.contains(&s.name))
+                .map(|s| s.used)
+                .sum();
+            format!(" (frees at least {})", crate::zfs::format_bytes(used, units))
+        }
+    }
+
     pub fn set_error(&mut self, message: String) {
+        log::error!("{}", message);
+        self.record_error(message.clone());
         self.error_message = Some(message);
         self.update_status_help_text();
     }
 
+    /// Keep an error in the error log without interrupting the user with it
+    pub fn record_error(&mut self, message: String) {
+        if self.error_log.len() == ERROR_LOG_LIMIT {
+            self.error_log.pop_front();
+        }
+        self.error_log.push_back(LoggedError { at: Instant::now(), message });
+    }
+
     pub fn clear_error(&mut self) {
         self.error_message = None;
         self.update_status_help_text();
     }
 
+    pub fn set_info(&mut self, message: String) {
+        self.info_message = Some(message);
+    }
+
     pub fn update_status_help_text(&mut self) {
         // Check for error first
         if let Some(error) = &self.error_message {
@@ -196,22 +513,40 @@ impl AppState {
         // Check for delete confirmation (only in snapshot view and not readonly)
         if self.delete_confirmation_pending && !self.config.readonly
             && let crate::state::AppView::SnapshotDetail(_, _) = &self.current_view {
-                if let Some(snapshot) = self.data_manager.snapshots.get(self.selected_snapshot_index) {
+                let window = self.delete_confirmation_window();
+                let freed = self.delete_space_summary();
+                let delete_key = self.keymap.label(Action::Delete, 1);
+                if !self.selected_snapshots.is_empty() {
+                    self.status_help_text = format!("⚠️  DELETE {} marked snapshots{}: Press '{}' again{} to CONFIRM", self.selected_snapshots.len(), freed, delete_key, window);
+                } else if let Some(snapshot) = self.data_manager.snapshots.get(self.selected_snapshot_index) {
                     let short_name = snapshot.name.split('@').next_back().unwrap_or(&snapshot.name);
-                    self.status_help_text = format!("⚠️  DELETE {}: Press 'd' again to CONFIRM", short_name);
+                    self.status_help_text = format!("⚠️  DELETE {}{}: Press '{}' again{} to CONFIRM", short_name, freed, delete_key, window);
                 } else {
-                    self.status_help_text = "⚠️  Press 'd' again to CONFIRM DELETION".to_string();
+                    self.status_help_text = format!("⚠️  Press '{}' again{} to CONFIRM DELETION", delete_key, window);
                 }
-                self.status_help_color = ratatui::style::Color::Yellow;
+                self.status_help_color = self.theme_manager.get_colors().warning;
                 return;
             }
 
         // Default status text
-        self.status_help_text = if self.config.readonly {
-            "↑/↓: Navigate | PgUp/PgDn: Page | s: Sort | ←/Esc: Back | h: Help | q: Quit (READONLY MODE)".to_string()
-        } else {
-            "↑/↓: Navigate | PgUp/PgDn: Page | d: Delete | s: Sort | ←/Esc: Back | h: Help | q: Quit".to_string()
-        };
+        self.status_help_text = navigation_help_text(self.config.readonly, &self.keymap);
         self.status_help_color = ratatui::style::Color::Reset;
     }
-}
\ No newline at end of file
+}
+
+/// Status bar key hints; destructive keys are left out in readonly mode since they do nothing there
+fn navigation_help_text(readonly: bool, keys: &Keymap) -> String {
+    let up_down = format!("{}/{}", keys.label(Action::NavigateUp, 1), keys.label(Action::NavigateDown, 1));
+    let common = format!(
+        "{}: Sort | {}: Back | {}: Help | {}: Quit",
+        keys.label(Action::Sort, 1),
+        keys.label(Action::Back, 2),
+        keys.label(Action::Help, 1),
+        keys.label(Action::Quit, 1)
+    );
+    if readonly {
+        format!("{}: Navigate | PgUp/PgDn: Page | {} (READONLY MODE)", up_down, common)
+    } else {
+        format!("{}: Navigate | PgUp/PgDn: Page | Space: Mark | {}: Delete | {}", up_down, keys.label(Action::Delete, 1), common)
+    }
+}
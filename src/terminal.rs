@@ -0,0 +1,85 @@
+use anyhow::Result;
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::{Backend, CrosstermBackend}, Terminal};
+use std::io::{self, Write};
+use std::process::{Command, ExitStatus};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// A global rather than a field because the panic hook has to undo the capture with nothing
+// else to go on
+static MOUSE_CAPTURE: AtomicBool = AtomicBool::new(true);
+
+/// Choose whether the TUI captures the mouse; without it the terminal's text selection works
+pub fn set_mouse_capture(enabled: bool) {
+    MOUSE_CAPTURE.store(enabled, Ordering::Relaxed);
+}
+
+pub struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+pub fn restore_terminal() {
+    let _ = disable_raw_mode();
+    if MOUSE_CAPTURE.load(Ordering::Relaxed) {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+    }
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, crossterm::cursor::Show);
+}
+
+fn enter_terminal() -> Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    if MOUSE_CAPTURE.load(Ordering::Relaxed) {
+        execute!(io::stdout(), EnableMouseCapture)?;
+    }
+    Ok(())
+}
+
+/// Restore the terminal before the panic message is printed; otherwise it lands on the
+/// alternate screen and the shell is left in raw mode
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+    }));
+}
+
+pub fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
+    enter_terminal()?;
+    let backend = CrosstermBackend::new(io::stdout());
+    Ok(Terminal::new(backend)?)
+}
+
+/// Hand the normal screen and cooked mode to `f`, then take the terminal back and force a
+/// full redraw, since whatever `f` printed has replaced ratatui's idea of the screen
+pub fn with_suspended_terminal<B: Backend, T>(terminal: &mut Terminal<B>, f: impl FnOnce() -> T) -> Result<T> {
+    restore_terminal();
+    let result = f();
+    enter_terminal()?;
+    terminal.clear()?;
+    Ok(result)
+}
+
+/// Run `command` through the user's shell with the terminal's stdio, then wait for Enter so
+/// its output can be read before the TUI comes back
+pub fn run_shell_command(command: &str) -> Result<ExitStatus> {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    log::info!("Shell command: {}", command);
+    println!("$ {}", command);
+    let status = Command::new(shell).arg("-c").arg(command).status()?;
+
+    print!("\n[{}] Press Enter to return", status);
+    io::stdout().flush()?;
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    Ok(status)
+}
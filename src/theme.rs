@@ -1,59 +1,212 @@
+use anyhow::{anyhow, Context, Result};
 use ratatui::style::Color;
+use serde::Deserialize;
+use std::path::Path;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum Theme {
+    #[default]
     Dark,
     Light,
+    /// Okabe-Ito palette: bars and highlights sit on the blue/yellow axis that deuteranopia
+    /// and protanopia leave intact
+    ColorblindSafe,
+    /// Terminal default colors only; chosen automatically under NO_COLOR or TERM=dumb
+    Monochrome,
+    Custom { name: String, colors: ThemeColors },
 }
 
-impl Default for Theme {
-    fn default() -> Self {
-        Self::Dark
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ThemeColors {
+    pub background: Color,
     pub text: Color,
     pub accent: Color,
     pub highlight: Color,
     pub border: Color,
     pub selected: Color,
+    pub warning: Color,
+    // Usage bar segments; the dataset's own data uses `accent`
+    pub children_segment: Color,
+    pub refreservation_segment: Color,
+    pub snapshot_segment: Color,
 }
 
+/// Segment colors for palette files that don't set their own
+const DEFAULT_CHILDREN_SEGMENT: Color = Color::Green;
+const DEFAULT_REFRESERVATION_SEGMENT: Color = Color::Magenta;
+const DEFAULT_SNAPSHOT_SEGMENT: Color = Color::Yellow;
+
 impl Theme {
     pub const fn get_colors(&self) -> ThemeColors {
         match self {
             Theme::Dark => ThemeColors {
+                background: Color::Reset,
                 text: Color::Reset,
                 accent: Color::Cyan,
                 highlight: Color::Blue,
                 border: Color::Gray,
                 selected: Color::Yellow,
+                warning: Color::Yellow,
+                children_segment: DEFAULT_CHILDREN_SEGMENT,
+                refreservation_segment: DEFAULT_REFRESERVATION_SEGMENT,
+                snapshot_segment: DEFAULT_SNAPSHOT_SEGMENT,
             },
             Theme::Light => ThemeColors {
+                background: Color::Reset,
                 text: Color::Reset,
                 accent: Color::Blue,
                 highlight: Color::LightBlue,
                 border: Color::DarkGray,
                 selected: Color::Magenta,
+                warning: Color::Red,
+                children_segment: DEFAULT_CHILDREN_SEGMENT,
+                refreservation_segment: DEFAULT_REFRESERVATION_SEGMENT,
+                snapshot_segment: DEFAULT_SNAPSHOT_SEGMENT,
+            },
+            Theme::ColorblindSafe => ThemeColors {
+                background: Color::Reset,
+                text: Color::Reset,
+                accent: Color::Rgb(86, 180, 233),
+                highlight: Color::Rgb(0, 114, 178),
+                border: Color::Gray,
+                selected: Color::Rgb(240, 228, 66),
+                warning: Color::Rgb(213, 94, 0),
+                // Bluish green, reddish purple and orange stay apart from the sky blue dataset segment
+                children_segment: Color::Rgb(0, 158, 115),
+                refreservation_segment: Color::Rgb(204, 121, 167),
+                snapshot_segment: Color::Rgb(230, 159, 0),
             },
+            Theme::Monochrome => ThemeColors {
+                background: Color::Reset,
+                text: Color::Reset,
+                accent: Color::Reset,
+                highlight: Color::Reset,
+                border: Color::Reset,
+                selected: Color::Reset,
+                warning: Color::Reset,
+                children_segment: Color::Reset,
+                refreservation_segment: Color::Reset,
+                snapshot_segment: Color::Reset,
+            },
+            Theme::Custom { colors, .. } => *colors,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+            Theme::ColorblindSafe => "Colorblind Safe",
+            Theme::Monochrome => "Monochrome",
+            Theme::Custom { name, .. } => name,
         }
     }
 }
 
-#[derive(Default)]
+/// Palette file as written by the user; every color but the bar segments is required
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PaletteFile {
+    name: Option<String>,
+    background: String,
+    text: String,
+    accent: String,
+    highlight: String,
+    border: String,
+    selected: String,
+    warning: String,
+    children_segment: Option<String>,
+    refreservation_segment: Option<String>,
+    snapshot_segment: Option<String>,
+}
+
+/// Load a custom theme from a TOML palette file.
+///
+/// The file is a flat table of hex color strings such as `background = "#1e1e2e"`. Every
+/// color is required except the bar segment colors; `name` is optional and defaults to the
+/// file stem.
+pub fn load_theme_file(path: &Path) -> Result<Theme> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read theme file {}", path.display()))?;
+    parse_theme(&contents, path)
+}
+
+/// Build a custom theme from the contents of the palette file at `path`
+fn parse_theme(contents: &str, path: &Path) -> Result<Theme> {
+    let palette: PaletteFile = toml::from_str(contents)
+        .with_context(|| format!("Invalid theme file {}", path.display()))?;
+    let color = |value: &str, key: &str| {
+        parse_hex_color(value).with_context(|| format!("{}: invalid color for `{}`", path.display(), key))
+    };
+    let segment = |value: &Option<String>, key: &str, default: Color| value.as_deref().map_or(Ok(default), |value| color(value, key));
+
+    Ok(Theme::Custom {
+        name: palette.name.clone().unwrap_or_else(|| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "Custom".to_string())
+        }),
+        colors: ThemeColors {
+            background: color(&palette.background, "background")?,
+            text: color(&palette.text, "text")?,
+            accent: color(&palette.accent, "accent")?,
+            highlight: color(&palette.highlight, "highlight")?,
+            border: color(&palette.border, "border")?,
+            selected: color(&palette.selected, "selected")?,
+            warning: color(&palette.warning, "warning")?,
+            children_segment: segment(&palette.children_segment, "children_segment", DEFAULT_CHILDREN_SEGMENT)?,
+            refreservation_segment: segment(&palette.refreservation_segment, "refreservation_segment", DEFAULT_REFRESERVATION_SEGMENT)?,
+            snapshot_segment: segment(&palette.snapshot_segment, "snapshot_segment", DEFAULT_SNAPSHOT_SEGMENT)?,
+        },
+    })
+}
+
+/// Parse a `#rrggbb` hex string into an RGB color.
+pub fn parse_hex_color(value: &str) -> Result<Color> {
+    let hex = value
+        .strip_prefix('#')
+        .ok_or_else(|| anyhow!("color `{}` must start with '#'", value))?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!("color `{}` must be in the form #rrggbb", value));
+    }
+
+    let component = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16);
+    Ok(Color::Rgb(component(0..2)?, component(2..4)?, component(4..6)?))
+}
+
 pub struct ThemeManager {
+    pub themes: Vec<Theme>,
     pub current_theme: Theme,
     pub selected_theme_index: usize,
 }
 
+impl Default for ThemeManager {
+    fn default() -> Self {
+        let current_theme = if no_color_requested() { Theme::Monochrome } else { Theme::default() };
+        Self {
+            themes: vec![Theme::Dark, Theme::Light, Theme::ColorblindSafe, Theme::Monochrome],
+            current_theme,
+            selected_theme_index: 0,
+        }
+    }
+}
+
+/// NO_COLOR (https://no-color.org) set to anything non-empty, or a terminal that declares
+/// itself unable to do color
+fn no_color_requested() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+        || std::env::var("TERM").is_ok_and(|term| term == "dumb")
+}
 
 impl ThemeManager {
     pub fn new() -> Self {
         Self::default()
     }
 
+    pub fn add_theme(&mut self, theme: Theme) {
+        self.themes.push(theme);
+    }
+
     pub fn get_colors(&self) -> ThemeColors {
         self.current_theme.get_colors()
     }
@@ -65,23 +218,126 @@ impl ThemeManager {
     }
 
     pub fn next_theme(&mut self) {
-        if self.selected_theme_index < 1 { // We have 2 themes (0-1)
+        if self.selected_theme_index + 1 < self.themes.len() {
             self.selected_theme_index += 1;
         }
     }
 
     pub fn select_theme(&mut self) {
-        self.current_theme = match self.selected_theme_index {
-            0 => Theme::Dark,
-            1 => Theme::Light,
-            _ => Theme::Light,
-        };
+        if let Some(theme) = self.themes.get(self.selected_theme_index) {
+            self.current_theme = theme.clone();
+        }
     }
 
     pub fn set_selected_index_from_theme(&mut self) {
-        self.selected_theme_index = match self.current_theme {
-            Theme::Dark => 0,
-            Theme::Light => 1,
-        };
+        self.selected_theme_index = self
+            .themes
+            .iter()
+            .position(|theme| *theme == self.current_theme)
+            .unwrap_or(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PALETTE: &str = "\
+name = \"Mocha\"
+background = \"#1e1e2e\"
+text = \"#cdd6f4\"
+accent = \"#89b4fa\"
+highlight = \"#45475a\"
+border = \"#6c7086\"
+selected = \"#f5c2e7\"
+warning = \"#f38ba8\" # red
+";
+
+    fn parse(contents: &str) -> Result<Theme> {
+        parse_theme(contents, Path::new("mocha.toml"))
+    }
+
+    #[test]
+    fn parse_hex_color_reads_rrggbb() {
+        assert_eq!(parse_hex_color("#1e1e2e").unwrap(), Color::Rgb(0x1e, 0x1e, 0x2e));
+        assert_eq!(parse_hex_color("#FFA500").unwrap(), Color::Rgb(255, 165, 0));
+    }
+
+    #[test]
+    fn parse_hex_color_rejects_malformed_values() {
+        assert!(parse_hex_color("1e1e2e").is_err());
+        assert!(parse_hex_color("#1e1e2").is_err());
+        assert!(parse_hex_color("#1e1e2e0").is_err());
+        assert!(parse_hex_color("#gg0000").is_err());
+        // Multibyte input must be rejected rather than sliced mid-character
+        assert!(parse_hex_color("#ééé").is_err());
+    }
+
+    #[test]
+    fn palette_file_becomes_a_custom_theme() {
+        let theme = parse(PALETTE).unwrap();
+
+        assert_eq!(theme.name(), "Mocha");
+        assert_eq!(theme.get_colors().background, Color::Rgb(0x1e, 0x1e, 0x2e));
+        assert_eq!(theme.get_colors().warning, Color::Rgb(0xf3, 0x8b, 0xa8));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn unknown_keys_and_missing_colors_are_errors() {
+        let unknown = parse(&format!("{}shadow = \"#000000\"\n", PALETTE)).err().unwrap();
+        assert!(format!("{:#}", unknown).contains("unknown field `shadow`"));
+
+        let missing = parse(&PALETTE.replace("border = \"#6c7086\"\n", "")).err().unwrap();
+        assert!(format!("{:#}", missing).contains("missing field `border`"));
+
+        let invalid = parse(&PALETTE.replace("#6c7086", "#6c708")).err().unwrap();
+        assert!(format!("{:#}", invalid).contains("invalid color for `border`"));
+    }
+
+    #[test]
+    fn palette_accepts_any_toml_string_syntax() {
+        let literal = PALETTE.replace("\"#1e1e2e\"", "'#1e1e2e'").replace("name = \"Mocha\"", "name = \"\"\"Mocha\"\"\"");
+        let theme = parse(&literal).unwrap();
+        assert_eq!(theme.name(), "Mocha");
+        assert_eq!(theme.get_colors().background, Color::Rgb(0x1e, 0x1e, 0x2e));
+
+        // Unquoted values are not TOML strings
+        assert!(parse(&PALETTE.replace("\"#89b4fa\"", "#89b4fa")).is_err());
+    }
+
+    #[test]
+    fn colorblind_safe_segments_use_the_okabe_ito_palette() {
+        let colors = Theme::ColorblindSafe.get_colors();
+        let okabe_ito = [
+            Color::Rgb(230, 159, 0),
+            Color::Rgb(86, 180, 233),
+            Color::Rgb(0, 158, 115),
+            Color::Rgb(240, 228, 66),
+            Color::Rgb(0, 114, 178),
+            Color::Rgb(213, 94, 0),
+            Color::Rgb(204, 121, 167),
+        ];
+
+        let segments = [colors.accent, colors.children_segment, colors.refreservation_segment, colors.snapshot_segment];
+        assert!(segments.iter().all(|color| okabe_ito.contains(color)));
+        for (i, color) in segments.iter().enumerate() {
+            assert!(!segments[i + 1..].contains(color));
+        }
+    }
+
+    #[test]
+    fn segment_colors_are_optional() {
+        let theme = parse(PALETTE).unwrap();
+        assert_eq!(theme.get_colors().snapshot_segment, DEFAULT_SNAPSHOT_SEGMENT);
+
+        let theme = parse(&format!("{}snapshot_segment = \"#fab387\"\n", PALETTE)).unwrap();
+        assert_eq!(theme.get_colors().snapshot_segment, Color::Rgb(0xfa, 0xb3, 0x87));
+        assert_eq!(theme.get_colors().children_segment, DEFAULT_CHILDREN_SEGMENT);
+    }
+
+    #[test]
+    fn name_defaults_to_the_file_stem() {
+        let theme = parse(&PALETTE.replace("name = \"Mocha\"\n", "")).unwrap();
+        assert_eq!(theme.name(), "mocha");
+    }
+}
@@ -1,71 +1,246 @@
 mod utils;
 
+use std::collections::HashMap;
+
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
     Frame,
 };
 
 use crate::{
+    config::{BarStyle, ByteUnits},
+    keymap::Action,
+    data::{DatasetScalingValues, SnapshotScalingValues},
     state::{AppState, AppView},
-    zfs::format_bytes,
+    theme::Theme,
+    zfs::{format_bytes, format_optional_bytes},
 };
 
 use utils::*;
 
-const DATASET_VIEW_FIXED_WIDTH: usize = 79;
-const SNAPSHOT_VIEW_FIXED_WIDTH: usize = 54;
-const STATUS_BAR_HEIGHT: u16 = 3;
+// Borders, highlight symbol, bar labels and bar brackets
+const DATASET_VIEW_FIXED_WIDTH: usize = 19;
+const SNAPSHOT_VIEW_FIXED_WIDTH: usize = 21;
+// Borders, highlight symbol, spacing, bar brackets and the capacity/free/frag/health suffix
+const POOL_VIEW_FIXED_WIDTH: usize = 49;
+// Right-aligned "(42 snaps)" column after the dataset name
+const SNAPSHOT_COUNT_WIDTH: usize = 13;
+// " A:" label and the right-aligned available space after the dataset bars
+const AVAILABLE_WIDTH: usize = 13;
+// Right-aligned share of the pool's usable space, e.g. " 12.5%"
+const POOL_SHARE_WIDTH: usize = 7;
+const DATASET_BAR_COUNT: usize = 3;
+const SNAPSHOT_BAR_COUNT: usize = 3;
+// Percentage of the row given to bars rather than the name column
+const DATASET_BAR_SHARE_PERCENT: usize = 60;
+const SNAPSHOT_BAR_SHARE_PERCENT: usize = 60;
+// Rows (or columns) taken by the status bar border; the status and help lines inside wrap
+// onto at most MAX_STATUS_LINES rows
+const STATUS_BAR_BORDERS: u16 = 2;
+const MAX_STATUS_LINES: u16 = 4;
+const BREADCRUMB_HEIGHT: u16 = 1;
 const HELP_CONTENT_PERCENTAGE: u16 = 70;
 const THEME_SELECTION_PERCENTAGE: u16 = 30;
+const MODAL_WIDTH_PERCENTAGE: u16 = 80;
+const MODAL_HEIGHT_PERCENTAGE: u16 = 80;
+const PREFETCH_BAR_WIDTH: usize = 10;
+const INPUT_HEIGHT: u16 = 3;
 
 pub fn draw(f: &mut Frame, app: &mut AppState) {
+    let status_lines = status_bar_lines(app);
+    let status_height = status_bar_height(&status_lines, f.area().width);
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(STATUS_BAR_HEIGHT)])
+        .constraints([
+            Constraint::Length(BREADCRUMB_HEIGHT),
+            Constraint::Min(0),
+            Constraint::Length(status_height),
+        ])
         .split(f.area());
 
-    let visible_height = chunks[0].height.saturating_sub(2) as usize;
+    let colors = app.theme_manager.get_colors();
+    f.render_widget(Block::default().style(Style::default().bg(colors.background)), f.area());
+
+    let visible_height = chunks[1].height.saturating_sub(2) as usize;
     app.update_scroll(visible_height);
+    app.list_area = chunks[1];
+
+    draw_breadcrumb(f, chunks[0], app);
 
     match &app.current_view {
-        AppView::PoolList => draw_pool_list(f, chunks[0], app),
-        AppView::DatasetView(pool_name) => draw_dataset_view(f, chunks[0], app, pool_name),
+        AppView::PoolList => draw_pool_list(f, chunks[1], app),
+        AppView::DatasetView(pool_name) => draw_dataset_view(f, chunks[1], app, pool_name),
         AppView::SnapshotDetail(pool_name, dataset_name) => {
-            draw_snapshot_detail(f, chunks[0], app, pool_name, dataset_name)
+            draw_snapshot_detail(f, chunks[1], app, pool_name, dataset_name)
         }
-        AppView::Help => draw_help_screen(f, chunks[0], app),
+        AppView::AllSnapshots => draw_all_snapshots(f, chunks[1], app),
+        AppView::Help => draw_help_screen(f, chunks[1], app),
     }
 
-    draw_status_bar(f, chunks[1], app);
+    draw_modal(f, chunks[1], app);
+    draw_input(f, chunks[1], app);
+    draw_status_bar(f, chunks[2], app, status_lines);
+
+    if app.theme_manager.current_theme == Theme::Monochrome {
+        strip_colors(f.buffer_mut());
+    }
+}
+
+/// Drop every color, including the fixed segment and highlight colors that themes don't cover.
+/// Cells that relied on a background, such as the selected row, are shown reversed instead.
+fn strip_colors(buffer: &mut ratatui::buffer::Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
+}
+
+/// Build the Pool > Dataset > Snapshot path for the current view, ending at the selected item
+fn breadcrumb_parts(app: &AppState) -> Vec<String> {
+    let mut parts: Vec<String> = match &app.current_view {
+        AppView::PoolList => {
+            return app.data_manager.pools.get(app.selected_pool_index).map(|p| p.name.clone()).into_iter().collect();
+        }
+        AppView::DatasetView(pool_name) => {
+            let mut parts = vec![pool_name.clone()];
+            parts.extend(app.data_manager.datasets.get(app.selected_dataset_index).map(|d| d.name.clone()));
+            parts
+        }
+        AppView::SnapshotDetail(pool_name, dataset_name) => {
+            let mut parts = vec![pool_name.clone(), dataset_name.clone()];
+            parts.extend(app.data_manager.snapshots.get(app.selected_snapshot_index).map(|s| s.name.clone()));
+            parts
+        }
+        AppView::AllSnapshots => {
+            let mut parts = vec!["All Snapshots".to_string()];
+            parts.extend(app.data_manager.snapshots.get(app.selected_snapshot_index).map(|s| s.name.clone()));
+            return parts;
+        }
+        AppView::Help => return vec!["Help".to_string()],
+    };
+    // The root dataset shares its name with the pool
+    parts.dedup();
+    parts
+}
+
+fn draw_breadcrumb(f: &mut Frame, area: Rect, app: &AppState) {
+    let colors = app.theme_manager.get_colors();
+    let breadcrumb = truncate_with_ellipsis(&breadcrumb_parts(app).join(" > "), area.width as usize);
+    let paragraph = Paragraph::new(Line::from(Span::styled(breadcrumb, Style::default().fg(colors.accent))));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_modal(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let colors = app.theme_manager.get_colors();
+    let Some(modal) = app.modal.as_mut() else {
+        return;
+    };
+
+    let modal_area = centered_rect(MODAL_WIDTH_PERCENTAGE, MODAL_HEIGHT_PERCENTAGE, area);
+    let visible_height = modal_area.height.saturating_sub(2) as usize;
+    modal.scroll_offset = modal.scroll_offset.min(modal.lines.len().saturating_sub(visible_height));
+
+    // Tabs would otherwise be passed straight through and misalign the terminal
+    let lines: Vec<Line> = modal
+        .lines
+        .iter()
+        .map(|line| Line::from(line.replace('\t', "    ")))
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(modal.title.as_str())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(colors.accent)),
+        )
+        .style(Style::default().fg(colors.text).bg(colors.background))
+        .scroll((modal.scroll_offset as u16, 0));
+
+    f.render_widget(Clear, modal_area);
+    f.render_widget(paragraph, modal_area);
+}
+
+fn draw_input(f: &mut Frame, area: Rect, app: &AppState) {
+    let colors = app.theme_manager.get_colors();
+    let Some(input) = &app.input else {
+        return;
+    };
+
+    let input_area = Rect {
+        height: INPUT_HEIGHT.min(area.height),
+        ..centered_rect(MODAL_WIDTH_PERCENTAGE, 0, area)
+    };
+    let line = Line::from(vec![
+        Span::styled(input.prefix.as_str(), Style::default().fg(colors.border)),
+        Span::styled(input.value.as_str(), Style::default().fg(colors.text)),
+        Span::styled("█", Style::default().fg(colors.accent)),
+    ]);
+
+    let paragraph = Paragraph::new(line)
+        .block(
+            Block::default()
+                .title(input.title.as_str())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(colors.accent)),
+        )
+        .style(Style::default().fg(colors.text).bg(colors.background));
+
+    f.render_widget(Clear, input_area);
+    f.render_widget(paragraph, input_area);
 }
 
 fn draw_pool_list(f: &mut Frame, area: Rect, app: &AppState) {
     let colors = app.theme_manager.get_colors();
+    let units = app.config.units;
+
+    if app.data_manager.is_loading_pools() {
+        let message = format!("{} Loading pools...", spinner_frame(app.tick));
+        draw_empty_state(f, area, "ZFS Pools".to_string(), &message, &colors);
+        return;
+    }
+
+    if app.data_manager.pools.is_empty() {
+        draw_empty_state(f, area, "ZFS Pools".to_string(), "No ZFS pools found", &colors);
+        return;
+    }
 
     let max_name_width = calculate_max_pool_name_width(&app.data_manager.pools);
+    let bar_width = (area.width as usize)
+        .saturating_sub(POOL_VIEW_FIXED_WIDTH + max_name_width)
+        .max(MIN_BAR_WIDTH);
 
     let items: Vec<ListItem> = app
         .data_manager
         .pools
         .iter()
         .map(|pool| {
-            let usage_percent = if pool.size > 0 {
-                pool.allocated as f64 / pool.size as f64 * 100.0
+            // Usable figures may be missing (e.g. no root dataset info); fall back to raw sizes
+            let (used, size) = match (app.show_usable_capacity, pool.usable_used, pool.usable_size) {
+                (true, Some(used), Some(size)) => (used, size),
+                _ => (pool.allocated, pool.size),
+            };
+            let usage_percent = if size > 0 {
+                used as f64 / size as f64 * 100.0
             } else {
                 0.0
             };
 
             // Use actual percentage for bar scaling (0-100%)
-            let bar_chars = (BAR_WIDTH as f64 * usage_percent / 100.0) as usize;
+            let bar_chars = bar_width as f64 * usage_percent / 100.0;
 
             // Create text to overlay on the bar
-            let bar_text = format!("{}/{}", format_bytes(pool.allocated), format_bytes(pool.size));
+            let bar_text = format!("{}/{}", format_bytes(used, units), format_bytes(size, units));
             let usage_bar_spans = create_progress_bar_with_text(
                 bar_chars,
-                '█',
+                bar_width,
+                app.config.bar_style,
                 bar_text,
                 colors.accent,  // Background color for filled portion
                 Color::White    // Text color
@@ -73,7 +248,7 @@ fn draw_pool_list(f: &mut Frame, area: Rect, app: &AppState) {
 
             let mut content_spans = vec![
                 Span::styled(
-                    format!("{:<width$}", pool.name, width = max_name_width),
+                    pad_to_width(&pool.name, max_name_width),
                     Style::default().fg(colors.text),
                 ),
                 Span::raw(" "),
@@ -84,7 +259,13 @@ fn draw_pool_list(f: &mut Frame, area: Rect, app: &AppState) {
 
             // Add remaining info after the bar
             content_spans.push(Span::styled(
-                format!(" ({:>3.0}%) [{}]", usage_percent, pool.health),
+                format!(
+                    " ({:>3}%) free {} frag {:>3} [{}]",
+                    if app.show_usable_capacity { usage_percent.round() as u64 } else { pool.capacity },
+                    format_bytes(pool.free, units),
+                    pool.fragmentation.map_or_else(|| "-".to_string(), |frag| format!("{}%", frag)),
+                    pool.health
+                ),
                 Style::default().fg(colors.text),
             ));
 
@@ -94,10 +275,20 @@ fn draw_pool_list(f: &mut Frame, area: Rect, app: &AppState) {
         })
         .collect();
 
+    let title = format!(
+        "ZFS Pools (Sort: {}, {} capacity, u: toggle)",
+        app.sort_manager.get_pool_sort_indicator(),
+        if app.show_usable_capacity { "usable" } else { "raw" }
+    );
+
+    let (health, all_online) = pool_health_summary(&app.data_manager.pools);
+    let health_color = if all_online { colors.text } else { colors.warning };
+
     let pools_list = List::new(items)
         .block(
             Block::default()
-                .title("ZFS Pools")
+                .title(title)
+                .title_top(Line::styled(health, Style::default().fg(health_color)).right_aligned())
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(colors.border)),
         )
@@ -113,27 +304,65 @@ fn draw_pool_list(f: &mut Frame, area: Rect, app: &AppState) {
 
 fn draw_dataset_view(f: &mut Frame, area: Rect, app: &AppState, pool_name: &str) {
     let colors = app.theme_manager.get_colors();
+
+    if app.data_manager.datasets.is_empty() {
+        draw_empty_state(f, area, format!("Datasets in Pool: {}", pool_name), "No datasets found in this pool", &colors);
+        return;
+    }
     let visible_height = area.height.saturating_sub(2) as usize;
     let (start, end) = app.get_visible_range(app.data_manager.datasets.len(), visible_height);
-    let scaling_values = calculate_dataset_scaling(&app.data_manager.datasets);
-    let name_width = calculate_dataset_name_width(area.width as usize);
+    let pool = app.data_manager.pools.iter().find(|p| p.name == pool_name);
+    // Pool scaling needs the usable size; without it the bars stay relative to the largest dataset
+    let pool_size = pool.and_then(|pool| pool.usable_size).filter(|_| app.scale_to_pool);
+    let pool_scaling = pool_size.map(DatasetScalingValues::relative_to);
+    let scaling_values = pool_scaling.as_ref().unwrap_or(&app.data_manager.dataset_scaling);
+    let columns = calculate_column_widths(
+        area.width as usize,
+        DATASET_VIEW_FIXED_WIDTH + SNAPSHOT_COUNT_WIDTH + AVAILABLE_WIDTH + POOL_SHARE_WIDTH,
+        DATASET_BAR_COUNT,
+        DATASET_BAR_SHARE_PERCENT,
+    );
 
     let items = create_dataset_list_items(
         &app.data_manager.datasets[start..end],
         pool_name,
-        &scaling_values,
-        name_width,
-        &colors
+        pool,
+        scaling_values,
+        &columns,
+        &colors,
+        app,
     );
 
     let sort_indicator = app.sort_manager.get_dataset_sort_indicator();
 
-    let title = format!("Datasets in Pool: {} (Sort: {})", pool_name, sort_indicator);
+    let scale = if pool_size.is_some() { "pool size" } else { "largest" };
+    let hidden = if app.hide_inactive { ", hiding inactive" } else { "" };
+    let mut title_spans = vec![Span::raw(format!("Datasets in Pool: {} (Sort: {}, Scale: {}{})", pool_name, sort_indicator, scale, hidden))];
+    if app.show_usage_breakdown {
+        title_spans.extend([
+            Span::raw(" ["),
+            Span::styled("█ Dataset ", Style::default().fg(colors.accent)),
+            Span::styled("█ Children ", Style::default().fg(colors.children_segment)),
+            Span::styled("█ Refreservation ", Style::default().fg(colors.refreservation_segment)),
+            Span::styled("█ Snapshots", Style::default().fg(colors.snapshot_segment)),
+            Span::raw("]"),
+        ]);
+    } else {
+        title_spans.extend([
+            Span::raw(" ["),
+            Span::styled("█ Dataset ", Style::default().fg(colors.accent)),
+            Span::styled("█ Snapshots", Style::default().fg(colors.snapshot_segment)),
+            Span::raw("]"),
+        ]);
+    }
+    let title = Line::from(title_spans);
+    let summary = dataset_summary(&app.data_manager.datasets, &app.data_manager.pool_datasets, app.config.units);
 
     let datasets_list = List::new(items)
         .block(
             Block::default()
                 .title(title)
+                .title_bottom(summary)
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(colors.border)),
         )
@@ -147,6 +376,23 @@ fn draw_dataset_view(f: &mut Frame, area: Rect, app: &AppState, pool_name: &str)
     }
 
     f.render_stateful_widget(datasets_list, area, &mut list_state);
+    draw_scrollbar(f, area, app.data_manager.datasets.len(), visible_height, start, &colors);
+}
+
+/// Scrollbar over the list's right border, only when the items don't all fit
+fn draw_scrollbar(f: &mut Frame, area: Rect, total: usize, visible_height: usize, offset: usize, colors: &crate::theme::ThemeColors) {
+    if total <= visible_height {
+        return;
+    }
+    // Positions run over the possible scroll offsets, so the thumb reaches the end with the list
+    let mut scrollbar_state = ScrollbarState::new(total - visible_height + 1)
+        .viewport_content_length(visible_height)
+        .position(offset);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .style(Style::default().fg(colors.border));
+    f.render_stateful_widget(scrollbar, area.inner(Margin { vertical: 1, horizontal: 0 }), &mut scrollbar_state);
 }
 
 fn draw_snapshot_detail(
@@ -156,27 +402,58 @@ fn draw_snapshot_detail(
     _pool_name: &str,
     dataset_name: &str,
 ) {
+    let heading = format!("Snapshots in Dataset: {}", dataset_name);
+    draw_snapshot_list(f, area, app, &heading, "No snapshots found for this dataset", false);
+}
+
+fn draw_all_snapshots(f: &mut Frame, area: Rect, app: &AppState) {
+    let heading = if app.data_manager.is_prefetch_complete() {
+        "All Snapshots".to_string()
+    } else {
+        "All Snapshots (prefetch still running; reopen to include more)".to_string()
+    };
+    draw_snapshot_list(f, area, app, &heading, "No snapshots cached yet; prefetch fills this list", true);
+}
+
+/// Snapshot list shared by the per-dataset and all-snapshots views; `full_names` keeps the
+/// dataset part of each name, which the per-dataset view leaves out
+fn draw_snapshot_list(f: &mut Frame, area: Rect, app: &AppState, heading: &str, empty_message: &str, full_names: bool) {
     let colors = app.theme_manager.get_colors();
+
+    if app.data_manager.snapshots.is_empty() {
+        draw_empty_state(f, area, heading.to_string(), empty_message, &colors);
+        return;
+    }
+
     let visible_height = area.height.saturating_sub(2) as usize;
     let (start, end) = app.get_visible_range(app.data_manager.snapshots.len(), visible_height);
-    let scaling_values = calculate_snapshot_scaling(&app.data_manager.snapshots);
-    let name_width = calculate_snapshot_name_width(area.width as usize);
+    let scaling_values = &app.data_manager.snapshot_scaling;
+    let columns = calculate_column_widths(
+        area.width as usize,
+        SNAPSHOT_VIEW_FIXED_WIDTH,
+        SNAPSHOT_BAR_COUNT,
+        SNAPSHOT_BAR_SHARE_PERCENT,
+    );
 
     let items = create_snapshot_list_items(
         &app.data_manager.snapshots[start..end],
-        &scaling_values,
-        name_width,
-        &colors
+        scaling_values,
+        &columns,
+        &colors,
+        app,
+        full_names,
     );
 
     let sort_indicator = app.sort_manager.get_snapshot_sort_indicator();
 
-    let title = format!("Snapshots in Dataset: {} (Sort: {})", dataset_name, sort_indicator);
+    let title = format!("{} (Sort: {})", heading, sort_indicator);
+    let summary = snapshot_summary(&app.data_manager.snapshots, app.config.units);
 
     let snapshots_list = List::new(items)
         .block(
             Block::default()
                 .title(title)
+                .title_bottom(summary)
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(colors.border)),
         )
@@ -190,38 +467,118 @@ fn draw_snapshot_detail(
     }
 
     f.render_stateful_widget(snapshots_list, area, &mut list_state);
+    draw_scrollbar(f, area, app.data_manager.snapshots.len(), visible_height, start, &colors);
 }
 
+fn describe_mountpoint(dataset: &crate::zfs::Dataset) -> Option<String> {
+    match dataset.mountpoint.as_str() {
+        // Volumes have no mountpoint
+        "-" => None,
+        "none" => Some("Not mountable (mountpoint=none)".to_string()),
+        "legacy" => Some("Legacy mount (managed by fstab)".to_string()),
+        path if dataset.mounted => Some(format!("Mounted at {}", path)),
+        path => Some(format!("Not mounted ({})", path)),
+    }
+}
+
+fn draw_empty_state(f: &mut Frame, area: Rect, title: String, message: &str, colors: &crate::theme::ThemeColors) {
+    let paragraph = Paragraph::new(Line::from(Span::styled(message, Style::default().fg(colors.text))))
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(colors.border)),
+        );
+
+    f.render_widget(paragraph, area);
+}
+
+/// Rows the status bar needs so its wrapped lines are not cut off on a narrow terminal
+fn status_bar_height(lines: &[Line], width: u16) -> u16 {
+    let inner_width = width.saturating_sub(STATUS_BAR_BORDERS).max(1);
+    // Measure with the same word wrapping the status bar is drawn with
+    let rows = Paragraph::new(lines.to_vec()).wrap(Wrap { trim: true }).line_count(inner_width);
+    rows.min(MAX_STATUS_LINES as usize) as u16 + STATUS_BAR_BORDERS
+}
+
+fn draw_status_bar(f: &mut Frame, area: Rect, app: &AppState, lines: Vec<Line<'static>>) {
+    let colors = app.theme_manager.get_colors();
+    let status = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(colors.border)),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(status, area);
+}
 
-fn draw_status_bar(f: &mut Frame, area: Rect, app: &AppState) {
+/// The status line for the current view, followed by its key help
+fn status_bar_lines(app: &AppState) -> Vec<Line<'static>> {
     let colors = app.theme_manager.get_colors();
-    let prefetch_status = if app.data_manager.is_prefetch_complete() {
-        "".to_string()
+    let prefetch_status = if app.data_manager.is_loading_pools() {
+        format!(" [{} Loading pools...]", spinner_frame(app.tick))
+    } else if app.data_manager.is_prefetch_complete() {
+        match app.data_manager.prefetch_failure_count() {
+            0 => "".to_string(),
+            failed => format!(" [{} failed to load (press e for details)]", failed),
+        }
     } else {
         let (completed, total) = app.data_manager.get_prefetch_progress();
         if total > 0 {
-            format!(" [Loading snapshots for dataset {} of {}...]", completed, total)
+            format!(
+                " [{} Loading snapshots {} {}% ({} of {})]",
+                spinner_frame(app.tick),
+                mini_progress_bar(completed, total, PREFETCH_BAR_WIDTH),
+                completed * 100 / total,
+                completed,
+                total
+            )
         } else {
-            " [Loading snapshots...]".to_string()
+            format!(" [{} Loading snapshots...]", spinner_frame(app.tick))
         }
     };
 
+    // Appended after the prefetch progress, so it shows in every view
+    let prefetch_status = match &app.available_update {
+        Some(version) => format!("{} [Update available: v{} (run `update`)]", prefetch_status, version),
+        None => prefetch_status,
+    };
+
+    // Hints follow the keymap so remapped keys are shown as bound
+    let keys = &app.keymap;
+    let up_down = format!("{}/{}", keys.label(Action::NavigateUp, 1), keys.label(Action::NavigateDown, 1));
+    let forward = keys.label(Action::Forward, 2);
+    let back = keys.label(Action::Back, 2);
+    let sort = keys.label(Action::Sort, 1);
+    let help = keys.label(Action::Help, 1);
+    let quit = keys.label(Action::Quit, 1);
+
     let (status_text, help_text, help_color) = match &app.current_view {
         AppView::PoolList => {
             let total = app.data_manager.pools.len();
             let current = if total > 0 { app.selected_pool_index + 1 } else { 0 };
             (
                 format!("Pool List ({}/{}){}",  current, total, prefetch_status),
-                "↑/↓: Navigate | PgUp/PgDn: Page | →/Enter: View Datasets | h: Help | q: Quit".to_string(),
+                format!("{}: Navigate | PgUp/PgDn: Page | {}: View Datasets | a: All Snapshots | p: Status | {}: Sort | {}: Help | {}: Quit", up_down, forward, sort, help, quit),
                 Color::Reset
             )
         },
         AppView::DatasetView(pool_name) => {
             let total = app.data_manager.datasets.len();
             let current = if total > 0 { app.selected_dataset_index + 1 } else { 0 };
+            let mount_status = app
+                .data_manager
+                .datasets
+                .get(app.selected_dataset_index)
+                .and_then(describe_mountpoint)
+                .map(|description| format!(" | {}", description))
+                .unwrap_or_default();
             (
-                format!("Datasets in {} ({}/{}){}",  pool_name, current, total, prefetch_status),
-                "↑/↓: Navigate | PgUp/PgDn: Page | →/Enter: View Snapshots | s: Sort | ←/Esc: Back | h: Help | q: Quit".to_string(),
+                format!("Datasets in {} ({}/{}){}{}",  pool_name, current, total, mount_status, prefetch_status),
+                format!("{}: Navigate | PgUp/PgDn: Page | {}: View Snapshots | [/]: Pool | i: Info | b: Breakdown | {}: Sort | {}: Back | {}: Help | {}: Quit", up_down, forward, sort, back, help, quit),
                 Color::Reset
             )
         },
@@ -236,15 +593,25 @@ fn draw_status_bar(f: &mut Frame, area: Rect, app: &AppState) {
             } else {
                 let total = app.data_manager.snapshots.len();
                 let current = if total > 0 { app.selected_snapshot_index + 1 } else { 0 };
+                let marked_status = if app.selected_snapshots.is_empty() {
+                    "".to_string()
+                } else {
+                    format!(" [{} marked]", app.selected_snapshots.len())
+                };
+                let window = app.delete_confirmation_window();
                 let status_text = if app.delete_confirmation_pending {
-                    if let Some(snapshot) = app.data_manager.snapshots.get(app.selected_snapshot_index) {
+                    let freed = app.delete_space_summary();
+                    let delete_key = keys.label(Action::Delete, 1);
+                    if !app.selected_snapshots.is_empty() {
+                        format!("⚠️  DELETE {} marked snapshots{}: Press '{}' again{} to CONFIRM - Snapshots in {} ({}/{}){}", app.selected_snapshots.len(), freed, delete_key, window, dataset_name, current, total, prefetch_status)
+                    } else if let Some(snapshot) = app.data_manager.snapshots.get(app.selected_snapshot_index) {
                         let short_name = snapshot.name.split('@').next_back().unwrap_or(&snapshot.name);
-                        format!("⚠️  DELETE {}: Press 'd' again to CONFIRM - Snapshots in {} ({}/{}){}", short_name, dataset_name, current, total, prefetch_status)
+                        format!("⚠️  DELETE {}{}: Press '{}' again{} to CONFIRM - Snapshots in {} ({}/{}){}", short_name, freed, delete_key, window, dataset_name, current, total, prefetch_status)
                     } else {
-                        format!("⚠️  Press 'd' again to CONFIRM DELETION - Snapshots in {} ({}/{}){}", dataset_name, current, total, prefetch_status)
+                        format!("⚠️  Press '{}' again{} to CONFIRM DELETION - Snapshots in {} ({}/{}){}", delete_key, window, dataset_name, current, total, prefetch_status)
                     }
                 } else {
-                    format!("Snapshots in {} ({}/{}){}",  dataset_name, current, total, prefetch_status)
+                    format!("Snapshots in {} ({}/{}){}{}",  dataset_name, current, total, marked_status, prefetch_status)
                 };
                 (
                     status_text,
@@ -253,32 +620,66 @@ fn draw_status_bar(f: &mut Frame, area: Rect, app: &AppState) {
                 )
             }
         },
+        AppView::AllSnapshots => {
+            let total = app.data_manager.snapshots.len();
+            let current = if total > 0 { app.selected_snapshot_index + 1 } else { 0 };
+            (
+                format!("All cached snapshots ({}/{}){}", current, total, prefetch_status),
+                format!("{}: Navigate | PgUp/PgDn: Page | {}: Open Dataset | {}/S: Sort | y: Copy | {}: Back | {}: Help | {}: Quit", up_down, forward, sort, back, help, quit),
+                Color::Reset
+            )
+        },
         AppView::Help => (
             format!("Help & Settings{}", prefetch_status),
-            "↑/↓: Select Theme | Enter: Apply Theme | ←/Esc: Back | q: Quit".to_string(),
+            format!("{}: Select Theme | {}: Apply Theme | PgUp/PgDn: Scroll Help | {}: Back | {}: Quit", up_down, forward, back, quit),
             Color::Reset
         ),
     };
 
-    let status = Paragraph::new(vec![
-        Line::from(vec![
-            Span::styled(&status_text, Style::default().fg(colors.accent)),
-        ]),
+    // Let the user know when rows are missing because they could not be parsed
+    let skipped_rows = app.data_manager.skipped_row_count();
+    let status_text = if skipped_rows > 0 {
+        format!("{} [{} unparseable rows skipped]", status_text, skipped_rows)
+    } else {
+        status_text
+    };
+
+    let (status_text, help_text, help_color) = match (&app.input, &app.modal) {
+        (Some(input), _) => (
+            format!("{}{}", input.title, prefetch_status),
+            "Enter: Confirm | Esc: Cancel".to_string(),
+            Color::Reset,
+        ),
+        (None, Some(modal)) => (
+            format!("{} ({} lines){}", modal.title, modal.lines.len(), prefetch_status),
+            format!("{}: Scroll | PgUp/PgDn: Page | {}: Close | {}: Quit", up_down, back, quit),
+            Color::Reset,
+        ),
+        (None, None) => (status_text, help_text, help_color),
+    };
+
+    // Informational messages take the place of the status line until the next key press
+    let status_text = app.info_message.clone().unwrap_or(status_text);
+
+    // Keep readonly mode visible in every view so a disabled 'd' isn't a surprise
+    let mut status_spans = Vec::new();
+    if app.config.readonly {
+        status_spans.push(Span::styled("[READONLY] ", Style::default().fg(colors.warning).add_modifier(Modifier::BOLD)));
+    }
+    if app.config.dry_run {
+        status_spans.push(Span::styled("[DRY RUN] ", Style::default().fg(colors.warning).add_modifier(Modifier::BOLD)));
+    }
+    status_spans.push(Span::styled(status_text, Style::default().fg(colors.accent)));
+
+    vec![
+        Line::from(status_spans),
         Line::from(vec![
-            Span::styled(&help_text, Style::default().fg(help_color)),
+            Span::styled(help_text, Style::default().fg(help_color)),
         ]),
-    ])
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(colors.border)),
-    )
-    .wrap(Wrap { trim: true });
-
-    f.render_widget(status, area);
+    ]
 }
 
-fn draw_help_screen(f: &mut Frame, area: Rect, app: &AppState) {
+fn draw_help_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
     let colors = app.theme_manager.get_colors();
 
     // Split area into help content and theme selection
@@ -287,16 +688,55 @@ fn draw_help_screen(f: &mut Frame, area: Rect, app: &AppState) {
         .constraints([Constraint::Percentage(HELP_CONTENT_PERCENTAGE), Constraint::Percentage(THEME_SELECTION_PERCENTAGE)].as_ref())
         .split(area);
 
+    // Remappable keys are listed as currently bound
+    let keys = &app.keymap;
+    let key_line = |keys: String, description: &str| Line::from(format!("  {:<14} {}", keys, description));
+
     // Help content
-    let help_text = vec![
+    let mut help_text = vec![
         Line::from(vec![Span::styled("ZFS Space Visualizer", Style::default().fg(colors.accent).add_modifier(Modifier::BOLD))]),
+        Line::from(format!("  Version {} ({}); --version-verbose prints full build details", env!("CARGO_PKG_VERSION"), crate::build_info::commit())),
         Line::from(""),
         Line::from("NAVIGATION:"),
-        Line::from("  ↑/↓ or j/k     Navigate up/down"),
-        Line::from("  →/Enter        Go forward/select"),
-        Line::from("  ←/Esc/Backspace Go back"),
-        Line::from("  h              Show this help"),
-        Line::from("  q or Ctrl+C    Quit application"),
+        key_line(keys.label(Action::NavigateUp, usize::MAX), "Navigate up"),
+        key_line(keys.label(Action::NavigateDown, usize::MAX), "Navigate down"),
+        Line::from("  PgUp/PgDn      Page up/down"),
+        Line::from("  gg/G or Home/End Jump to first/last item"),
+        key_line(keys.label(Action::Forward, usize::MAX), "Go forward/select"),
+        key_line(keys.label(Action::Back, usize::MAX), "Go back"),
+        key_line(keys.label(Action::Help, usize::MAX), "Show this help"),
+        key_line(keys.label(Action::Sort, usize::MAX), "Cycle sort order"),
+        Line::from("  S              Reverse sort direction"),
+        Line::from("  [/]            Switch to the previous/next pool's datasets"),
+        Line::from("  p              Show zpool status of selected pool"),
+        Line::from("  I              Show live read/write throughput of selected pool"),
+        Line::from("  e              Show recent errors, including background prefetch failures"),
+        Line::from("  V              Show version and build details"),
+        Line::from("  a              List all cached snapshots across pools (from the pool list)"),
+        Line::from("                 Enter on one opens its dataset's snapshot list"),
+        Line::from("  i              Show exact sizes, a dataset's properties and clone origin, a snapshot's clones"),
+        Line::from("  f              Diff selected snapshot against the previous one"),
+        Line::from("  y              Copy selected name to clipboard"),
+        Line::from("  b              Toggle dataset usage breakdown"),
+        Line::from("  u              Toggle raw/usable pool capacity"),
+        Line::from("  v              Scale dataset bars to the largest dataset or the pool size"),
+        Line::from("  t              Toggle dataset tree view (Space folds a subtree)"),
+        Line::from("  H              Hide datasets with canmount=off or no space used"),
+        Line::from("  /name          Jump to dataset by name prefix"),
+    ];
+    // Don't advertise keys that readonly mode disables
+    if !app.config.readonly {
+        help_text.push(Line::from("  c              Create a child of the selected dataset"));
+        help_text.push(Line::from("  X              Destroy the selected dataset (type its name to confirm)"));
+        help_text.push(Line::from("  r              Rename the selected snapshot"));
+        help_text.push(Line::from("  !              Run a shell command with the TUI suspended"));
+        help_text.push(Line::from("  Space          Mark snapshot for batch delete"));
+        help_text.push(Line::from("  P              Mark snapshots older than a number of days for deletion"));
+        help_text.push(key_line(keys.label(Action::Delete, usize::MAX), "Delete selected/marked snapshots"));
+        help_text.push(key_line(format!("y (after {})", keys.label(Action::Delete, 1)), "Copy the zfs destroy commands the delete will run"));
+    }
+    help_text.extend([
+        key_line(format!("{} or Ctrl+C", keys.label(Action::Quit, usize::MAX)), "Quit application"),
         Line::from(""),
         Line::from("VIEWS:"),
         Line::from("  Pool List      Shows all ZFS pools with usage"),
@@ -305,39 +745,62 @@ fn draw_help_screen(f: &mut Frame, area: Rect, app: &AppState) {
         Line::from(""),
         Line::from("LEGEND:"),
         Line::from("  Pool List:"),
-        Line::from("    Usage: █ Space used / total capacity"),
+        Line::from("    Usage: █ Space used / total capacity (raw, or usable after redundancy with u)"),
+        Line::from("    (cap%) free space, fragmentation% and [health]"),
         Line::from(""),
         Line::from("  Dataset View:"),
         Line::from("    D: █ Dataset-only data (excludes snapshots)"),
-        Line::from("    S: █ Snapshot data (used by snapshots)"),
-        Line::from("    T: █ Total used space (dataset + snapshots)"),
+        Line::from("    S: █ Snapshot data (used by snapshots), in the snapshot color"),
+        Line::from("    T: █ Total used space: dataset part, then snapshot part"),
+        Line::from("    A:   Space left before the quota or the pool is full"),
+        Line::from("    %:   Share of the pool's usable space used by the dataset and its children"),
+        Line::from("    Names in the warning color are near their quota or pool limit"),
+        Line::from("    U: █ Used space by dataset, children, refreservation and snapshots (b)"),
         Line::from(""),
         Line::from("  Snapshot View:"),
         Line::from("    U: █ Used space (actual disk usage)"),
         Line::from("    R: █ Referenced data (logical size)"),
-    ];
+        Line::from("    W: █ Written since the previous snapshot"),
+        Line::from("    Names in the warning color are older than --stale-days (default 90)"),
+    ]);
+
+    let visible_height = chunks[0].height.saturating_sub(2) as usize;
+    let total_lines = help_text.len();
+    app.help_scroll_offset = app.help_scroll_offset.min(total_lines.saturating_sub(visible_height));
+    let title = if total_lines > visible_height {
+        format!(
+            "Help (lines {}-{} of {}, Shift+↑/↓ or PgUp/PgDn to scroll)",
+            app.help_scroll_offset + 1,
+            (app.help_scroll_offset + visible_height).min(total_lines),
+            total_lines
+        )
+    } else {
+        "Help".to_string()
+    };
 
+    // Wrapping would make the line count unknowable, so long lines are clipped instead
     let help_paragraph = Paragraph::new(help_text)
         .block(
             Block::default()
-                .title("Help")
+                .title(title)
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(colors.border)),
         )
         .style(Style::default().fg(colors.text))
-        .wrap(Wrap { trim: true });
+        .scroll((app.help_scroll_offset as u16, 0));
 
     f.render_widget(help_paragraph, chunks[0]);
 
     // Theme selection
-    let themes = ["Dark", "Light"];
-    let theme_items: Vec<ListItem> = themes
+    let theme_items: Vec<ListItem> = app
+        .theme_manager
+        .themes
         .iter()
         .enumerate()
-        .map(|(i, theme_name)| {
+        .map(|(i, theme)| {
             let content = vec![Line::from(vec![
                 Span::styled(
-                    format!("  {}", theme_name),
+                    format!("  {}", theme.name()),
                     if i == app.theme_manager.selected_theme_index {
                         Style::default().fg(colors.selected).add_modifier(Modifier::BOLD)
                     } else {
@@ -362,10 +825,7 @@ fn draw_help_screen(f: &mut Frame, area: Rect, app: &AppState) {
     let theme_list = List::new(theme_items)
         .block(
             Block::default()
-                .title(format!("Theme (Current: {})", match app.theme_manager.current_theme {
-                    crate::theme::Theme::Dark => "Dark",
-                    crate::theme::Theme::Light => "Light",
-                }))
+                .title(format!("Theme (Current: {})", app.theme_manager.current_theme.name()))
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(colors.border)),
         )
@@ -374,38 +834,58 @@ fn draw_help_screen(f: &mut Frame, area: Rect, app: &AppState) {
     f.render_widget(theme_list, chunks[1]);
 }
 
-struct DatasetScalingValues {
-    max_dataset_size: u64,
-    max_snapshot_size: u64,
-    max_total_size: u64,
+/// e.g. " 3 pools: 2 ONLINE, 1 DEGRADED ", and whether every pool is ONLINE
+fn pool_health_summary(pools: &[crate::zfs::Pool]) -> (String, bool) {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for pool in pools {
+        match counts.iter_mut().find(|(health, _)| *health == pool.health) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((&pool.health, 1)),
+        }
+    }
+    // Healthy pools first, then the rest in the order they were listed
+    counts.sort_by_key(|(health, _)| *health != "ONLINE");
+
+    let breakdown: Vec<String> = counts.iter().map(|(health, count)| format!("{} {}", count, health)).collect();
+    let noun = if pools.len() == 1 { "pool" } else { "pools" };
+    let all_online = counts.iter().all(|(health, _)| *health == "ONLINE");
+    (format!(" {} {}: {} ", pools.len(), noun, breakdown.join(", ")), all_online)
 }
 
-fn calculate_dataset_scaling(datasets: &[crate::zfs::Dataset]) -> DatasetScalingValues {
-    DatasetScalingValues {
-        max_dataset_size: datasets.iter().map(|d| d.referenced).max().unwrap_or(1),
-        max_snapshot_size: datasets.iter().map(|d| d.snapshot_used).max().unwrap_or(1),
-        max_total_size: datasets.iter().map(|d| d.referenced + d.snapshot_used).max().unwrap_or(1),
-    }
+/// Totals over the visible `rows`. A folded tree row carries its subtree's sizes, so each
+/// row's own values are looked up in `pool_datasets` instead.
+fn dataset_summary(rows: &[crate::zfs::Dataset], pool_datasets: &[crate::zfs::Dataset], units: ByteUnits) -> String {
+    let own: HashMap<&str, &crate::zfs::Dataset> = pool_datasets.iter().map(|d| (d.name.as_str(), d)).collect();
+    let datasets: Vec<&crate::zfs::Dataset> = rows.iter().map(|row| own.get(row.name.as_str()).copied().unwrap_or(row)).collect();
+    let referenced: u64 = datasets.iter().map(|d| d.referenced).sum();
+    let snapshot_used: u64 = datasets.iter().map(|d| d.snapshot_bytes()).sum();
+    format!(
+        " Total: D {} | S {} | T {} ",
+        format_bytes(referenced, units),
+        format_bytes(snapshot_used, units),
+        format_bytes(referenced + snapshot_used, units)
+    )
 }
 
-fn calculate_dataset_name_width(area_width: usize) -> usize {
-    if area_width > DATASET_VIEW_FIXED_WIDTH {
-        area_width - DATASET_VIEW_FIXED_WIDTH
-    } else {
-        MIN_NAME_WIDTH
-    }
+fn snapshot_summary(snapshots: &[crate::zfs::Snapshot], units: ByteUnits) -> String {
+    let used: u64 = snapshots.iter().map(|s| s.used).sum();
+    format!(" Total used: {} across {} snapshots ", format_bytes(used, units), snapshots.len())
 }
 
 fn create_dataset_list_items<'a>(
     datasets: &'a [crate::zfs::Dataset],
     pool_name: &'a str,
+    pool: Option<&crate::zfs::Pool>,
     scaling: &'a DatasetScalingValues,
-    name_width: usize,
+    columns: &ColumnWidths,
     colors: &'a crate::theme::ThemeColors,
+    app: &AppState,
 ) -> Vec<ListItem<'a>> {
+    let config = &app.config;
+    let units = config.units;
     datasets.iter().map(|dataset| {
         let dataset_only = dataset.referenced;
-        let snapshot_used = dataset.snapshot_used;
+        let snapshot_used = dataset.snapshot_bytes();
         let total_used = dataset_only + snapshot_used;
 
         let dataset_percent = if scaling.max_dataset_size > 0 {
@@ -424,39 +904,90 @@ fn create_dataset_list_items<'a>(
             0.0
         };
 
-        let dataset_chars = (BAR_WIDTH as f64 * dataset_percent / 100.0) as usize;
-        let snapshot_chars = (BAR_WIDTH as f64 * snapshot_percent / 100.0) as usize;
-        let total_chars = (BAR_WIDTH as f64 * total_percent / 100.0) as usize;
+        let dataset_chars = columns.bar as f64 * dataset_percent / 100.0;
+        let snapshot_chars = columns.bar as f64 * snapshot_percent / 100.0;
+        let total_chars = (columns.bar as f64 * total_percent / 100.0) as usize;
 
-        let dataset_text = format_bytes(dataset_only);
-        let snapshot_text = format_bytes(snapshot_used);
-        let total_text = format_bytes(total_used);
+        let dataset_text = format_bytes(dataset_only, units);
+        let snapshot_text = format_optional_bytes(dataset.snapshot_used, units);
+        let total_text = format_bytes(total_used, units);
 
         let dataset_bar_spans = create_progress_bar_with_text(
-            dataset_chars, '█', dataset_text, colors.accent, Color::White
+            dataset_chars, columns.bar, app.config.bar_style, dataset_text, colors.accent, Color::White
         );
         let snapshot_bar_spans = create_progress_bar_with_text(
-            snapshot_chars, '█', snapshot_text, colors.accent, Color::White
-        );
-        let total_bar_spans = create_progress_bar_with_text(
-            total_chars, '█', total_text, colors.accent, Color::White
+            snapshot_chars, columns.bar, app.config.bar_style, snapshot_text, colors.snapshot_segment, Color::White
         );
+        // Split the total bar in proportion, in the same colors as the D and S bars
+        let total_dataset_chars = if total_used > 0 {
+            (total_chars as f64 * dataset_only as f64 / total_used as f64).round() as usize
+        } else {
+            0
+        };
+        let total_segments = [
+            (total_dataset_chars, colors.accent),
+            (total_chars - total_dataset_chars, colors.snapshot_segment),
+        ];
+        let total_bar_spans = create_stacked_bar_with_text(&total_segments, columns.bar, app.config.bar_style, total_text, Color::White);
 
         let short_name = dataset.name.strip_prefix(pool_name)
             .unwrap_or(&dataset.name)
             .trim_start_matches('/');
 
-        let display_name = if short_name.is_empty() || short_name == pool_name {
+        let display_name = if app.tree_view {
+            tree_display_name(app, dataset, short_name.is_empty(), columns.name)
+        } else if short_name.is_empty() || short_name == pool_name {
             "(root dataset)".to_string()
         } else {
-            truncate_with_ellipsis(short_name, name_width)
+            truncate_with_ellipsis(short_name, columns.name)
         };
 
+        let name_color = if dataset.fullness_percent().is_some_and(|percent| percent >= config.warn_threshold as f64) {
+            colors.warning
+        } else {
+            colors.text
+        };
+
+        let snapshot_count_span = Span::styled(
+            format!("{:>width$}", snapshot_count_label(app, &dataset.name), width = SNAPSHOT_COUNT_WIDTH),
+            Style::default().fg(colors.text),
+        );
+        // A dataset at its quota or on a full pool has nothing left; make that stand out
+        let available_color = if dataset.available == Some(0) { colors.warning } else { colors.text };
+        // `used` already includes children, so this is the whole subtree's share
+        let pool_share = pool
+            .and_then(|pool| pool.usable_size)
+            .filter(|&size| size > 0)
+            .map_or_else(|| "-".to_string(), |size| format!("{:.1}%", dataset.used as f64 / size as f64 * 100.0));
+        let trailing_spans = [
+            Span::raw(" A:"),
+            Span::styled(
+                format!("{:>width$}", format_optional_bytes(dataset.available, units), width = AVAILABLE_WIDTH - 3),
+                Style::default().fg(available_color),
+            ),
+            Span::styled(format!("{:>width$}", pool_share, width = POOL_SHARE_WIDTH), Style::default().fg(colors.text)),
+        ];
+
+        if app.show_usage_breakdown {
+            let mut content_spans = vec![
+                Span::styled(
+                    pad_to_width(&display_name, columns.name),
+                    Style::default().fg(name_color),
+                ),
+                snapshot_count_span,
+                Span::raw(" U:"),
+            ];
+            content_spans.extend(create_usage_breakdown_bar(dataset, scaling, columns.bar, colors, units, config.bar_style));
+            content_spans.extend(trailing_spans);
+            return ListItem::new(vec![Line::from(content_spans)]);
+        }
+
         let mut content_spans = vec![
             Span::styled(
-                format!("{:<width$}", display_name, width = name_width),
-                Style::default().fg(colors.text),
+                pad_to_width(&display_name, columns.name),
+                Style::default().fg(name_color),
             ),
+            snapshot_count_span,
             Span::raw(" D:"),
         ];
 
@@ -465,37 +996,82 @@ fn create_dataset_list_items<'a>(
         content_spans.extend(snapshot_bar_spans);
         content_spans.push(Span::raw(" T:"));
         content_spans.extend(total_bar_spans);
+        content_spans.extend(trailing_spans);
 
         ListItem::new(vec![Line::from(content_spans)])
     }).collect()
 }
 
-struct SnapshotScalingValues {
-    max_used_size: u64,
-    max_referenced_size: u64,
+/// Last name component indented by depth, with a fold marker on datasets that have children
+fn tree_display_name(app: &AppState, dataset: &crate::zfs::Dataset, is_root: bool, width: usize) -> String {
+    let marker = if !crate::dataset_tree::has_children(&app.data_manager.pool_datasets, &dataset.name) {
+        "  "
+    } else if app.collapsed_datasets.contains(&dataset.name) {
+        "▸ "
+    } else {
+        "▾ "
+    };
+    let leaf = if is_root {
+        "(root dataset)"
+    } else {
+        dataset.name.rsplit('/').next().unwrap_or(&dataset.name)
+    };
+    let indent = "  ".repeat(crate::dataset_tree::depth(&dataset.name));
+    let leaf = truncate_with_ellipsis(leaf, width.saturating_sub(indent.len() + 2));
+    format!("{}{}{}", indent, marker, leaf)
 }
 
-fn calculate_snapshot_scaling(snapshots: &[crate::zfs::Snapshot]) -> SnapshotScalingValues {
-    SnapshotScalingValues {
-        max_used_size: snapshots.iter().map(|s| s.used).max().unwrap_or(1),
-        max_referenced_size: snapshots.iter().map(|s| s.referenced).max().unwrap_or(1),
+/// Snapshot count from the prefetch cache; datasets it hasn't reached yet show a placeholder
+fn snapshot_count_label(app: &AppState, dataset_name: &str) -> String {
+    match app.data_manager.cached_snapshot_count(dataset_name) {
+        Some(1) => "(1 snap)".to_string(),
+        Some(count) => format!("({} snaps)", count),
+        None if !app.data_manager.is_prefetch_complete() => "...".to_string(),
+        None => String::new(),
     }
 }
 
-fn calculate_snapshot_name_width(area_width: usize) -> usize {
-    if area_width > SNAPSHOT_VIEW_FIXED_WIDTH {
-        (area_width - SNAPSHOT_VIEW_FIXED_WIDTH).max(MIN_NAME_WIDTH)
-    } else {
-        MIN_NAME_WIDTH
-    }
+fn create_usage_breakdown_bar(
+    dataset: &crate::zfs::Dataset,
+    scaling: &DatasetScalingValues,
+    bar_width: usize,
+    colors: &crate::theme::ThemeColors,
+    units: ByteUnits,
+    style: BarStyle,
+) -> Vec<Span<'static>> {
+    // One stacked bar spans the same width as the three D/S/T bars and their labels
+    let breakdown_width = bar_width * DATASET_BAR_COUNT + 10;
+    let segment_chars = |bytes: u64| {
+        if scaling.max_used_size > 0 {
+            (breakdown_width as f64 * bytes as f64 / scaling.max_used_size as f64) as usize
+        } else {
+            0
+        }
+    };
+
+    let segments = [
+        (segment_chars(dataset.used_by_dataset), colors.accent),
+        (segment_chars(dataset.used_by_children), colors.children_segment),
+        (segment_chars(dataset.used_by_refreservation), colors.refreservation_segment),
+        (segment_chars(dataset.snapshot_bytes()), colors.snapshot_segment),
+    ];
+    let used = dataset.used_by_dataset + dataset.used_by_children + dataset.used_by_refreservation + dataset.snapshot_bytes();
+
+    create_stacked_bar_with_text(&segments, breakdown_width, style, format_bytes(used, units), Color::White)
 }
 
 fn create_snapshot_list_items<'a>(
     snapshots: &'a [crate::zfs::Snapshot],
     scaling: &'a SnapshotScalingValues,
-    name_width: usize,
+    columns: &ColumnWidths,
     colors: &'a crate::theme::ThemeColors,
+    app: &AppState,
+    full_names: bool,
 ) -> Vec<ListItem<'a>> {
+    let units = app.config.units;
+    let marked = &app.selected_snapshots;
+    // Snapshots created before this are drawn in the warning color
+    let stale_before = (app.config.stale_days > 0).then(|| crate::zfs::days_ago(app.config.stale_days));
     snapshots.iter().map(|snapshot| {
         let snapshot_used = snapshot.used;
         let snapshot_referenced = snapshot.referenced;
@@ -510,27 +1086,54 @@ fn create_snapshot_list_items<'a>(
         } else {
             0.0
         };
+        let written_percent = if scaling.max_written_size > 0 {
+            (snapshot.written as f64 / scaling.max_written_size as f64 * 100.0).min(100.0)
+        } else {
+            0.0
+        };
 
-        let used_chars = (BAR_WIDTH as f64 * used_percent / 100.0) as usize;
-        let referenced_chars = (BAR_WIDTH as f64 * referenced_percent / 100.0) as usize;
+        let used_chars = columns.bar as f64 * used_percent / 100.0;
+        let referenced_chars = columns.bar as f64 * referenced_percent / 100.0;
+        let written_chars = columns.bar as f64 * written_percent / 100.0;
 
-        let used_text = format_bytes(snapshot_used);
-        let referenced_text = format_bytes(snapshot_referenced);
+        let used_text = format_bytes(snapshot_used, units);
+        let referenced_text = format_bytes(snapshot_referenced, units);
+        let written_text = format_bytes(snapshot.written, units);
 
         let used_bar_spans = create_progress_bar_with_text(
-            used_chars, '█', used_text, colors.accent, Color::White
+            used_chars, columns.bar, app.config.bar_style, used_text, colors.accent, Color::White
         );
         let referenced_bar_spans = create_progress_bar_with_text(
-            referenced_chars, '█', referenced_text, colors.accent, Color::White
+            referenced_chars, columns.bar, app.config.bar_style, referenced_text, colors.accent, Color::White
+        );
+        let written_bar_spans = create_progress_bar_with_text(
+            written_chars, columns.bar, app.config.bar_style, written_text, colors.accent, Color::White
         );
 
-        let short_name = snapshot.name.split('@').next_back().unwrap_or(&snapshot.name);
-        let display_name = truncate_with_ellipsis(short_name, name_width);
+        let short_name = if full_names {
+            &snapshot.name
+        } else {
+            snapshot.name.split('@').next_back().unwrap_or(&snapshot.name)
+        };
+        let display_name = truncate_with_ellipsis(short_name, columns.name);
+
+        let marker = if marked.contains(&snapshot.name) {
+            Span::styled("● ", Style::default().fg(colors.selected))
+        } else {
+            Span::raw("  ")
+        };
+
+        let name_color = if snapshot.creation.zip(stale_before).is_some_and(|(creation, cutoff)| creation < cutoff) {
+            colors.warning
+        } else {
+            colors.text
+        };
 
         let mut content_spans = vec![
+            marker,
             Span::styled(
-                format!("{:<width$}", display_name, width = name_width),
-                Style::default().fg(colors.text),
+                pad_to_width(&display_name, columns.name),
+                Style::default().fg(name_color),
             ),
             Span::raw(" U:"),
         ];
@@ -538,8 +1141,30 @@ fn create_snapshot_list_items<'a>(
         content_spans.extend(used_bar_spans);
         content_spans.push(Span::raw(" R:"));
         content_spans.extend(referenced_bar_spans);
+        content_spans.push(Span::raw(" W:"));
+        content_spans.extend(written_bar_spans);
 
         ListItem::new(vec![Line::from(content_spans)])
     }).collect()
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_bar_height_counts_word_wrapped_rows() {
+        // 20 columns fit in two rows of 10, but whole words need three
+        let lines = [Line::from("aaaaaa bbbbbb cccccc")];
+
+        assert_eq!(status_bar_height(&lines, 10 + STATUS_BAR_BORDERS), 3 + STATUS_BAR_BORDERS);
+    }
+
+    #[test]
+    fn status_bar_height_is_capped() {
+        let lines = [Line::from("a b c d e f g h"), Line::from("help")];
+
+        assert_eq!(status_bar_height(&lines, 1 + STATUS_BAR_BORDERS), MAX_STATUS_LINES + STATUS_BAR_BORDERS);
+        assert_eq!(status_bar_height(&[Line::from("ok")], 80), 1 + STATUS_BAR_BORDERS);
+    }
+}
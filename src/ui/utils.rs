@@ -1,44 +1,84 @@
+use crate::config::BarStyle;
 use crate::zfs::Pool;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::Span,
 };
 
 pub const MIN_NAME_WIDTH: usize = 20;
-pub const BAR_WIDTH: usize = 20;
+pub const MIN_BAR_WIDTH: usize = 10;
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+pub fn spinner_frame(tick: usize) -> char {
+    SPINNER_FRAMES[tick % SPINNER_FRAMES.len()]
+}
+
+/// A compact text progress bar such as `███░░░░░░░` for use inside status lines
+pub fn mini_progress_bar(completed: usize, total: usize, width: usize) -> String {
+    let filled = (completed.min(total) * width).checked_div(total).unwrap_or(0);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
 
 pub fn calculate_max_pool_name_width(pools: &[Pool]) -> usize {
     pools
         .iter()
-        .map(|p| p.name.len())
+        .map(|p| p.name.width())
         .max()
         .unwrap_or(MIN_NAME_WIDTH)
         .max(MIN_NAME_WIDTH)
 }
 
+/// Widths of the name column and of each bar in a list row
+pub struct ColumnWidths {
+    pub name: usize,
+    pub bar: usize,
+}
+
+/// Split the space left after fixed overhead between the name column and `bar_count` bars.
+/// Bars get `bar_share_percent` of the space, never shrinking below their minimum width.
+pub fn calculate_column_widths(
+    area_width: usize,
+    fixed_overhead: usize,
+    bar_count: usize,
+    bar_share_percent: usize,
+) -> ColumnWidths {
+    let budget = area_width.saturating_sub(fixed_overhead);
+    let bar = (budget * bar_share_percent / 100 / bar_count).max(MIN_BAR_WIDTH);
+    let name = budget.saturating_sub(bar * bar_count).max(MIN_NAME_WIDTH);
+    ColumnWidths { name, bar }
+}
+
+/// Render a bar `filled` cells full, which may be fractional, with `text` right-justified over
+/// the top. Styles that draw partial cells show the fraction in the first unfilled cell.
 pub fn create_progress_bar_with_text(
-    filled_chars: usize,
-    fill_char: char,
+    filled: f64,
+    bar_width: usize,
+    style: BarStyle,
     text: String,
     filled_bg_color: Color,
     text_color: Color
 ) -> Vec<Span<'static>> {
     let mut spans = Vec::new();
+    let filled_chars = (filled.max(0.0) as usize).min(bar_width);
+    let fill_char = style.fill();
+    let partial_char = style.partial(filled.fract());
 
     // Add opening bracket
     spans.push(Span::raw("["));
 
     // Right-justify the text within the bar
-    let text_len = text.len().min(BAR_WIDTH);
-    let start_pos = BAR_WIDTH.saturating_sub(text_len);
+    let text_len = text.len().min(bar_width);
+    let start_pos = bar_width.saturating_sub(text_len);
 
-    let truncated_text = if text.len() > BAR_WIDTH {
-        text[..BAR_WIDTH].to_string()
+    let truncated_text = if text.len() > bar_width {
+        text[..bar_width].to_string()
     } else {
         text
     };
 
-    for i in 0..BAR_WIDTH {
+    for i in 0..bar_width {
         if i >= start_pos && i < start_pos + text_len {
             // Show text character overlaying the bar
             let text_char = truncated_text.chars().nth(i - start_pos).unwrap_or(' ');
@@ -65,6 +105,8 @@ pub fn create_progress_bar_with_text(
                     fill_char.to_string(),
                     Style::default().fg(filled_bg_color)
                 ));
+            } else if let Some(partial) = partial_char.filter(|_| i == filled_chars) {
+                spans.push(Span::styled(partial.to_string(), Style::default().fg(filled_bg_color)));
             } else {
                 spans.push(Span::raw(" "));
             }
@@ -77,9 +119,48 @@ pub fn create_progress_bar_with_text(
     spans
 }
 
+/// Render a bar made of consecutive colored segments, each given as (filled chars, color),
+/// with `text` right-justified over the top.
+pub fn create_stacked_bar_with_text(
+    segments: &[(usize, Color)],
+    width: usize,
+    style: BarStyle,
+    text: String,
+    text_color: Color
+) -> Vec<Span<'static>> {
+    let mut spans = vec![Span::raw("[")];
+
+    // Resolve the color of each cell up front so the text overlay can reuse it
+    let mut cell_colors: Vec<Option<Color>> = Vec::with_capacity(width);
+    for &(chars, color) in segments {
+        for _ in 0..chars {
+            if cell_colors.len() < width {
+                cell_colors.push(Some(color));
+            }
+        }
+    }
+    cell_colors.resize(width, None);
+
+    let text: String = text.chars().take(width).collect();
+    let text_len = text.chars().count();
+    let start_pos = width - text_len;
+
+    for (i, cell_color) in cell_colors.into_iter().enumerate() {
+        let text_char = (i >= start_pos).then(|| text.chars().nth(i - start_pos)).flatten();
+        spans.push(match (text_char, cell_color) {
+            (Some(c), Some(color)) => Span::styled(c.to_string(), Style::default().fg(text_color).bg(color)),
+            (Some(c), None) => Span::raw(c.to_string()),
+            (None, Some(color)) => Span::styled(style.fill().to_string(), Style::default().fg(color)),
+            (None, None) => Span::raw(" "),
+        });
+    }
+
+    spans.push(Span::raw("]"));
+    spans
+}
 
 pub fn truncate_with_ellipsis(text: &str, max_width: usize) -> String {
-    if text.len() <= max_width {
+    if text.width() <= max_width {
         return text.to_string();
     }
 
@@ -87,10 +168,109 @@ pub fn truncate_with_ellipsis(text: &str, max_width: usize) -> String {
         return "...".chars().take(max_width).collect();
     }
 
-    let half = (max_width - 3) / 2;
-    let start = &text[..half];
-    let end_start = text.len() - (max_width - 3 - half);
-    let end = &text[end_start..];
+    // Work in display columns on char boundaries so multibyte names never split mid-character
+    let available = max_width - 3;
+    let head_width = available / 2;
+    let tail_width = available - head_width;
+    let start = take_columns(text.chars(), head_width);
+    let end: String = take_columns(text.chars().rev(), tail_width).chars().rev().collect();
 
     format!("{}...{}", start, end)
-}
\ No newline at end of file
+}
+
+/// Left-align `text` in a column of `width` display columns; `{:<width$}` counts chars instead
+pub fn pad_to_width(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(text.width());
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// Collect chars until adding the next one would exceed `max_width` display columns
+fn take_columns(chars: impl Iterator<Item = char>, max_width: usize) -> String {
+    let mut taken = String::new();
+    let mut width = 0;
+    for c in chars {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width > max_width {
+            break;
+        }
+        width += char_width;
+        taken.push(c);
+    }
+    taken
+}
+
+/// Compute a rectangle centered in `area` taking the given percentage of its width and height
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_with_ellipsis_keeps_multibyte_chars_whole() {
+        assert_eq!(truncate_with_ellipsis("données-récentes", 10), "don...ntes");
+        assert_eq!(truncate_with_ellipsis("données", 10), "données");
+    }
+
+    #[test]
+    fn truncate_with_ellipsis_counts_double_width_columns() {
+        // Eight columns would cut through the second character on each side
+        let truncated = truncate_with_ellipsis("数据集备份", 8);
+        assert_eq!(truncated, "数...份");
+        assert!(truncated.width() <= 8);
+    }
+
+    fn rendered(spans: &[Span]) -> String {
+        spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    fn bar(filled: f64, style: BarStyle) -> String {
+        rendered(&create_progress_bar_with_text(filled, 10, style, String::new(), Color::Cyan, Color::White))
+    }
+
+    #[test]
+    fn partial_cells_are_drawn_in_eighths() {
+        assert_eq!(BarStyle::Block.partial(0.0), None);
+        assert_eq!(BarStyle::Block.partial(0.5), Some('▌'));
+        assert_eq!(BarStyle::Block.partial(0.99), Some('▉'));
+    }
+
+    #[test]
+    fn nearby_percentages_draw_different_bars() {
+        // 23.4% and 25% of ten cells both fill two whole cells; the partial cell tells them apart
+        assert_eq!(bar(2.34, BarStyle::Block), "[██▎       ]");
+        assert_eq!(bar(2.5, BarStyle::Block), "[██▌       ]");
+    }
+
+    #[test]
+    fn ascii_bars_round_down_to_whole_cells() {
+        assert_eq!(BarStyle::Hash.partial(0.5), None);
+        assert_eq!(bar(2.5, BarStyle::Hash), "[##        ]");
+        assert_eq!(bar(2.99, BarStyle::Equals), "[==        ]");
+    }
+
+    #[test]
+    fn pad_to_width_pads_by_display_columns() {
+        assert_eq!(pad_to_width("数据", 6), "数据  ");
+        assert_eq!(pad_to_width("é", 3), "é  ");
+        assert_eq!(pad_to_width("too wide", 3), "too wide");
+    }
+}
@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Result};
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use std::env;
+use std::path::{Path, PathBuf};
 use std::io::Write;
 use tempfile::NamedTempFile;
 
@@ -40,9 +42,20 @@ pub async fn check_and_update() -> Result<()> {
         .find(|a| a.name == asset_name)
         .ok_or_else(|| anyhow!("No asset found for current platform: {}", asset_name))?;
 
+    // Published next to each binary as `<asset>.sha256`, in `sha256sum` output format
+    let checksum_name = format!("{}.sha256", asset_name);
+    let checksum_asset = latest_release.assets
+        .iter()
+        .find(|a| a.name == checksum_name)
+        .ok_or_else(|| anyhow!("Release has no {}; refusing to install an unverified binary", checksum_name))?;
+
     println!("Downloading update from: {}", asset.browser_download_url);
     let binary_data = download_binary(&asset.browser_download_url).await?;
 
+    println!("Verifying checksum...");
+    let checksum_file = download_binary(&checksum_asset.browser_download_url).await?;
+    verify_checksum(&binary_data, &String::from_utf8_lossy(&checksum_file))?;
+
     println!("Replacing binary...");
     replace_current_binary(&binary_data)?;
 
@@ -52,6 +65,29 @@ pub async fn check_and_update() -> Result<()> {
     Ok(())
 }
 
+/// Latest released version when it is newer than this build, for the startup notice. Any
+/// failure, e.g. no network, is only logged.
+pub async fn newer_version() -> Option<String> {
+    let release = match fetch_latest_release().await {
+        Ok(release) => release,
+        Err(e) => {
+            log::debug!("Update check failed: {:#}", e);
+            return None;
+        }
+    };
+    let latest = release.tag_name.strip_prefix('v').unwrap_or(&release.tag_name);
+    is_newer(latest, env!("CARGO_PKG_VERSION")).then(|| latest.to_string())
+}
+
+/// Compare dotted numeric versions; anything unparseable is never considered newer
+fn is_newer(latest: &str, current: &str) -> bool {
+    let parse = |version: &str| version.split('.').map(|part| part.parse::<u64>().ok()).collect::<Option<Vec<_>>>();
+    match (parse(latest), parse(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
 async fn fetch_latest_release() -> Result<GitHubRelease> {
     let client = reqwest::Client::new();
     let response = client
@@ -84,6 +120,46 @@ async fn download_binary(url: &str) -> Result<Vec<u8>> {
     Ok(bytes.to_vec())
 }
 
+/// Check `data` against the first field of a `sha256sum` style line
+fn verify_checksum(data: &[u8], checksum_file: &str) -> Result<()> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow!("Checksum file is empty"))?
+        .to_ascii_lowercase();
+    let actual: String = Sha256::digest(data).iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    if actual != expected {
+        return Err(anyhow!(
+            "Checksum mismatch: expected {}, downloaded binary has {}; the update was not installed",
+            expected,
+            actual
+        ));
+    }
+    Ok(())
+}
+
+/// `<exe>.bak` next to the executable
+fn backup_path(exe: &Path) -> PathBuf {
+    let mut name = exe.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// Put the binary saved by the last update back in place. The version being replaced becomes
+/// the new backup, so a second rollback undoes the first.
+pub fn rollback() -> Result<()> {
+    let current_exe = env::current_exe()?;
+    let backup = backup_path(&current_exe);
+    let binary_data = std::fs::read(&backup)
+        .map_err(|e| anyhow!("No backup to roll back to at {}: {}", backup.display(), e))?;
+
+    println!("Restoring {}...", backup.display());
+    replace_current_binary(&binary_data)?;
+    println!("Rollback complete; the replaced version is now the backup.");
+    Ok(())
+}
+
 fn replace_current_binary(binary_data: &[u8]) -> Result<()> {
     let current_exe = env::current_exe()?;
 
@@ -107,6 +183,20 @@ fn replace_current_binary(binary_data: &[u8]) -> Result<()> {
         temp_file.as_file().set_permissions(perms)?;
     }
 
+    // Keep the running version so `update --rollback` can bring it back
+    let backup = backup_path(&current_exe);
+    std::fs::copy(&current_exe, &backup)
+        .map_err(|e| anyhow!("Could not back up the current binary to {}: {}", backup.display(), e))?;
+
+    // Windows can't overwrite a running executable but can rename it, so move it aside first;
+    // the leftover from the previous update is no longer running and can go
+    #[cfg(windows)]
+    {
+        let old_exe = current_exe.with_extension("old.exe");
+        let _ = std::fs::remove_file(&old_exe);
+        std::fs::rename(&current_exe, &old_exe)?;
+    }
+
     // Atomically replace the current binary
     let temp_path = temp_file.into_temp_path();
     temp_path.persist(&current_exe)?;
@@ -153,6 +243,7 @@ fn get_asset_name_for_platform() -> Result<String> {
         ("aarch64", "linux") => Ok("zfs_space_visualizer-aarch64-unknown-linux-musl".to_string()),
         ("x86_64", "macos") => Ok("zfs_space_visualizer-x86_64-apple-darwin".to_string()),
         ("aarch64", "macos") => Ok("zfs_space_visualizer-aarch64-apple-darwin".to_string()),
-        _ => Err(anyhow!("Unsupported platform: {}-{}", arch, os)),
+        ("x86_64", "windows") => Ok("zfs_space_visualizer-x86_64-pc-windows-msvc.exe".to_string()),
+        _ => Err(anyhow!("Self-update is not supported on this platform ({}-{})", arch, os)),
     }
 }
\ No newline at end of file
@@ -1,131 +1,640 @@
 use anyhow::{anyhow, Context, Result};
-use std::str;
-use tokio::process::Command;
+use serde::Deserialize;
+use std::{
+    env, fmt,
+    future::Future,
+    io,
+    process::Stdio,
+    str,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, BufReader},
+    process::Command,
+};
 
-#[derive(Debug, Clone)]
+use crate::{
+    config::{ByteUnits, DEFAULT_COMMAND_TIMEOUT_SECS},
+    fixture,
+};
+
+/// Returned when a ZFS utility such as `zpool` or `zfs` is not installed
+#[derive(Debug)]
+pub struct CommandNotFound {
+    pub command: String,
+}
+
+impl fmt::Display for CommandNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Command not found: {}", self.command)
+    }
+}
+
+impl std::error::Error for CommandNotFound {}
+
+/// Runs an external command and returns its stdout, so parsing can be tested without ZFS
+pub trait CommandRunner: Sync {
+    fn run(&self, command: &str, args: &[&str]) -> impl Future<Output = Result<String>> + Send;
+
+    /// Hand each line of stdout to `on_line` as it arrives. The default buffers via `run`;
+    /// real processes override it so huge listings never sit in memory as one string.
+    fn run_lines(
+        &self,
+        command: &str,
+        args: &[&str],
+        mut on_line: impl FnMut(&str) + Send,
+    ) -> impl Future<Output = Result<()>> + Send {
+        async move {
+            for line in self.run(command, args).await?.lines() {
+                on_line(line);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Runs commands as real child processes
+#[derive(Debug, Clone, Copy)]
+pub struct TokioCommandRunner {
+    // Seconds a command may run before it is killed, so a hung pool can't freeze the UI;
+    // zero disables the limit
+    timeout_secs: u64,
+}
+
+impl TokioCommandRunner {
+    pub fn new(timeout_secs: u64) -> Self {
+        Self { timeout_secs }
+    }
+}
+
+impl Default for TokioCommandRunner {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMMAND_TIMEOUT_SECS)
+    }
+}
+
+impl CommandRunner for TokioCommandRunner {
+    async fn run(&self, command: &str, args: &[&str]) -> Result<String> {
+        with_timeout(self.timeout_secs, command, args, run_to_completion(command, args)).await
+    }
+
+    async fn run_lines(&self, command: &str, args: &[&str], on_line: impl FnMut(&str) + Send) -> Result<()> {
+        with_timeout(self.timeout_secs, command, args, stream_lines(command, args, on_line)).await
+    }
+}
+
+/// Rows parsed from one `zfs`/`zpool` listing, along with the rows that had to be dropped
+#[derive(Debug)]
+pub struct Listing<T> {
+    pub rows: Vec<T>,
+    // Kept so the UI can explain entries missing from the listing
+    pub skipped: Vec<String>,
+}
+
+impl<T> Listing<T> {
+    fn new() -> Self {
+        Self { rows: Vec::new(), skipped: Vec::new() }
+    }
+
+    /// A listing served from fixture data, which has nothing to skip
+    fn complete(rows: Vec<T>) -> Self {
+        Self { rows, skipped: Vec::new() }
+    }
+
+    /// Parse one output row, keeping it aside when `parse` can't make sense of it
+    fn push_line(&mut self, line: &str, parse: impl FnOnce(&str) -> Option<T>) {
+        if line.trim().is_empty() {
+            return;
+        }
+        match parse(line) {
+            Some(row) => self.rows.push(row),
+            None => self.skip(line),
+        }
+    }
+
+    fn skip(&mut self, line: &str) {
+        log::warn!("Skipping unparseable row: {:?}", line);
+        self.skipped.push(line.to_owned());
+    }
+}
+
+/// Split a `-H` row into exactly `count` tab-separated fields. Splitting from the right keeps a
+/// leading name intact even if it contains a tab. A stray CR, trailing whitespace or trailing
+/// empty fields are dropped first; ZFS prints `-` rather than an empty value.
+fn split_fields(line: &str, count: usize) -> Option<Vec<&str>> {
+    let line = line.trim_end_matches(['\r', '\t', ' ']);
+    let mut fields: Vec<&str> = line.rsplitn(count, '\t').collect();
+    if fields.len() != count {
+        return None;
+    }
+    fields.reverse();
+    Some(fields)
+}
+
+/// Return the first ZFS utility that cannot be found on `PATH`, if any
+pub fn find_missing_command() -> Option<&'static str> {
+    let paths = env::var_os("PATH").unwrap_or_default();
+    ["zpool", "zfs"]
+        .into_iter()
+        .find(|command| !env::split_paths(&paths).any(|dir| dir.join(command).is_file()))
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
 pub struct Pool {
     pub name: String,
     pub size: u64,
     pub allocated: u64,
+    pub free: u64,
+    /// None when `zpool` reports "-" (e.g. pools without spacemap histograms)
+    pub fragmentation: Option<u64>,
+    pub capacity: u64,
     pub health: String,
+    /// Space consumed by the root dataset, after redundancy overhead
+    pub usable_used: Option<u64>,
+    /// Root dataset used+avail: what the pool can actually hold, unlike the raw RAIDZ `size`
+    pub usable_size: Option<u64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
 pub struct Dataset {
     pub name: String,
     pub referenced: u64,
-    pub snapshot_used: u64,
+    /// None when ZFS reports "-" rather than a value
+    pub snapshot_used: Option<u64>,
+    pub used_by_dataset: u64,
+    pub used_by_children: u64,
+    pub used_by_refreservation: u64,
+    pub mountpoint: String,
+    pub mounted: bool,
+    pub used: u64,
+    pub available: Option<u64>,
+    /// canmount=off, typically a container that only exists to hold children
+    pub mount_disabled: bool,
+    /// Snapshot a clone was created from; None for datasets that are not clones
+    pub origin: Option<String>,
+    /// Space guaranteed to the dataset and its descendants; 0 when unset
+    pub reservation: u64,
+    /// Space guaranteed to the dataset alone; 0 when unset
+    pub refreservation: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
 pub struct Snapshot {
     pub name: String,
     pub used: u64,
     pub referenced: u64,
+    /// Creation time in seconds since the Unix epoch; None when ZFS didn't report one
+    pub creation: Option<u64>,
+    /// Data written to the dataset between the previous snapshot and this one
+    pub written: u64,
+}
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Creation time before which a snapshot is more than `days` old
+pub fn days_ago(days: u64) -> u64 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    now.saturating_sub(days.saturating_mul(SECONDS_PER_DAY))
 }
 
-pub async fn get_pools() -> Result<Vec<Pool>> {
-    let output = execute_command("zpool", &["list", "-H", "-p"])
+pub async fn get_pools(runner: &TokioCommandRunner) -> Result<Listing<Pool>> {
+    if let Some(pools) = fixture::with_fixture(|f| f.pools.clone()) {
+        return Ok(Listing::complete(pools));
+    }
+    get_pools_with(runner).await
+}
+
+pub async fn get_pools_with(runner: &impl CommandRunner) -> Result<Listing<Pool>> {
+    let output = runner
+        .run("zpool", &["list", "-H", "-p", "-o", "name,size,alloc,free,frag,cap,health"])
         .await
         .context("Failed to list ZFS pools")?;
 
-    let mut pools = Vec::new();
+    let mut pools = Listing::new();
     for line in output.lines() {
-        if line.trim().is_empty() {
-            continue;
-        }
-        if let Some(pool_result) = parse_pool_line(line).await {
-            pools.push(pool_result?);
+        pools.push_line(line, parse_pool_line);
+    }
+
+    // Usable capacity is a nice-to-have; the raw pool list is still useful without it
+    if let Ok(output) = runner.run("zfs", &["list", "-H", "-p", "-d", "0", "-o", "name,used,avail"]).await {
+        for line in output.lines().filter(|line| !line.trim().is_empty()) {
+            let Some(fields) = split_fields(line, 3) else {
+                pools.skip(line);
+                continue;
+            };
+            if let Some(pool) = pools.rows.iter_mut().find(|pool| pool.name == fields[0]) {
+                let used = parse_u64(fields[1]);
+                pool.usable_used = used;
+                pool.usable_size = used.zip(parse_u64(fields[2])).map(|(used, avail)| used + avail);
+            }
         }
     }
+
     Ok(pools)
 }
 
-async fn parse_pool_line(line: &str) -> Option<Result<Pool>> {
-    let fields: Vec<&str> = line.split('\t').collect();
-    if fields.len() < 7 {
-        return None;
-    }
+fn parse_pool_line(line: &str) -> Option<Pool> {
+    let fields = split_fields(line, 7)?;
 
-    Some(Ok(Pool {
+    Some(Pool {
         name: fields[0].to_owned(),
-        size: parse_u64(fields[1]),
-        allocated: parse_u64(fields[2]),
-        health: fields[9].to_owned(),
-    }))
+        size: parse_u64(fields[1]).unwrap_or_default(),
+        allocated: parse_u64(fields[2]).unwrap_or_default(),
+        free: parse_u64(fields[3]).unwrap_or_default(),
+        fragmentation: parse_u64(fields[4].trim_end_matches('%')),
+        capacity: parse_u64(fields[5].trim_end_matches('%')).unwrap_or_default(),
+        health: fields[6].to_owned(),
+        ..Default::default()
+    })
+}
+/// Fetch the human-readable `zpool status` report for a pool
+pub async fn get_pool_status(runner: &TokioCommandRunner, pool_name: &str) -> Result<String> {
+    if fixture::is_active() {
+        return Ok(format!("  pool: {}\n state: fixture data, no device status available", pool_name));
+    }
+
+    runner
+        .run("zpool", &["status", pool_name])
+        .await
+        .with_context(|| format!("Failed to get status for pool {}", pool_name))
+}
+
+
+/// Per-second activity of a pool over one sampling interval
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoolIostat {
+    pub read_ops: u64,
+    pub write_ops: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Sample a pool's throughput over one second. Takes about a second to return.
+pub async fn get_pool_iostat(runner: &TokioCommandRunner, pool_name: &str) -> Result<PoolIostat> {
+    // Fixtures have no I/O; report an idle pool
+    if fixture::is_active() {
+        return Ok(PoolIostat::default());
+    }
+    get_pool_iostat_with(runner, pool_name).await
+}
+
+pub async fn get_pool_iostat_with(runner: &impl CommandRunner, pool_name: &str) -> Result<PoolIostat> {
+    let output = runner
+        .run("zpool", &["iostat", "-H", "-p", pool_name, "1", "2"])
+        .await
+        .with_context(|| format!("Failed to get I/O statistics for pool {}", pool_name))?;
+
+    // The first sample averages everything since import; only the second is a live rate
+    let line = output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .nth(1)
+        .ok_or_else(|| anyhow!("zpool iostat returned fewer than two samples"))?;
+    let fields = split_fields(line, 7).ok_or_else(|| anyhow!("Unexpected zpool iostat output: {}", line))?;
+    let field = |index: usize| parse_u64(fields[index]).unwrap_or_default();
+    Ok(PoolIostat {
+        read_ops: field(3),
+        write_ops: field(4),
+        read_bytes: field(5),
+        write_bytes: field(6),
+    })
 }
 
+pub async fn get_datasets(runner: &TokioCommandRunner, pool_name: &str) -> Result<Listing<Dataset>> {
+    if let Some(datasets) = fixture::with_fixture(|f| f.datasets_in(pool_name)) {
+        return Ok(Listing::complete(datasets));
+    }
+    get_datasets_with(runner, pool_name).await
+}
 
-pub async fn get_datasets(pool_name: &str) -> Result<Vec<Dataset>> {
-    let output = execute_command(
+pub async fn get_datasets_with(runner: &impl CommandRunner, pool_name: &str) -> Result<Listing<Dataset>> {
+    let output = runner.run(
         "zfs",
-        &["list", "-H", "-p", "-r", "-o", "name,refer,usedbysnapshots", pool_name],
+        &[
+            "list", "-H", "-p", "-r", "-o",
+            "name,refer,usedbysnapshots,usedbydataset,usedbychildren,usedbyrefreservation,mountpoint,mounted,used,avail,canmount,origin,reservation,refreservation",
+            pool_name,
+        ],
     )
     .await
     .with_context(|| format!("Failed to list datasets for pool {}", pool_name))?;
 
+    let mut datasets = Listing::new();
+    for line in output.lines() {
+        datasets.push_line(line, parse_dataset_line);
+    }
+    Ok(datasets)
+}
+
+fn parse_dataset_line(line: &str) -> Option<Dataset> {
+    let fields = split_fields(line, 14)?;
+    Some(Dataset {
+        name: fields[0].to_owned(),
+        referenced: parse_u64(fields[1]).unwrap_or_default(),
+        snapshot_used: parse_u64(fields[2]),
+        used_by_dataset: parse_u64(fields[3]).unwrap_or_default(),
+        used_by_children: parse_u64(fields[4]).unwrap_or_default(),
+        used_by_refreservation: parse_u64(fields[5]).unwrap_or_default(),
+        mountpoint: fields[6].to_owned(),
+        mounted: fields[7] == "yes",
+        used: parse_u64(fields[8]).unwrap_or_default(),
+        available: parse_u64(fields[9]),
+        mount_disabled: fields[10] == "off",
+        origin: Some(fields[11]).filter(|origin| *origin != "-").map(str::to_owned),
+        reservation: parse_u64(fields[12]).unwrap_or_default(),
+        refreservation: parse_u64(fields[13]).unwrap_or_default(),
+    })
+}
+
+
+impl Dataset {
+    /// Space used by snapshots, treating an unknown value as zero for arithmetic
+    pub fn snapshot_bytes(&self) -> u64 {
+        self.snapshot_used.unwrap_or(0)
+    }
+
+    /// Percentage of the space this dataset can use (used + available) that is already used,
+    /// or None when the available space is unknown
+    pub fn fullness_percent(&self) -> Option<f64> {
+        let capacity = self.used + self.available?;
+        Some(if capacity > 0 {
+            self.used as f64 / capacity as f64 * 100.0
+        } else {
+            0.0
+        })
+    }
+}
+
+/// Fetch every property of a dataset as `(property, value)` pairs
+pub async fn get_dataset_properties(runner: &TokioCommandRunner, dataset_name: &str) -> Result<Vec<(String, String)>> {
+    if let Some(dataset) = fixture::with_fixture(|f| f.datasets.iter().find(|d| d.name == dataset_name).cloned()) {
+        let dataset = dataset.ok_or_else(|| anyhow!("dataset does not exist: {}", dataset_name))?;
+        return Ok(vec![
+            ("name".to_string(), dataset.name),
+            ("used".to_string(), dataset.used.to_string()),
+            ("available".to_string(), dataset.available.map_or("-".to_string(), |a| a.to_string())),
+            ("referenced".to_string(), dataset.referenced.to_string()),
+            ("mountpoint".to_string(), dataset.mountpoint),
+        ]);
+    }
+
+    let output = runner.run(
+        "zfs",
+        &["get", "-H", "-o", "property,value", "all", dataset_name],
+    )
+    .await
+    .with_context(|| format!("Failed to get properties for dataset {}", dataset_name))?;
+
     Ok(output
         .lines()
         .filter(|line| !line.trim().is_empty())
-        .filter_map(parse_dataset_line)
+        .filter_map(|line| {
+            let (property, value) = line.split_once('\t')?;
+            Some((property.to_owned(), value.to_owned()))
+        })
         .collect())
 }
 
-fn parse_dataset_line(line: &str) -> Option<Dataset> {
-    let fields: Vec<&str> = line.split('\t').collect();
-    if fields.len() >= 3 {
-        Some(Dataset {
-            name: fields[0].to_owned(),
-            referenced: parse_u64(fields[1]),
-            snapshot_used: parse_u64(fields[2]),
-        })
+/// Record a destructive command that dry-run mode skipped
+fn log_dry_run(command: &str, args: &[&str]) {
+    log::info!("Dry run, not running: {}", command_line(command, args));
+}
+
+/// A command as it would be typed in a shell, quoting arguments that need it
+fn command_line(command: &str, args: &[&str]) -> String {
+    let quote = |arg: &str| {
+        let safe = |c: char| c.is_ascii_alphanumeric() || "-_.:/@%+=".contains(c);
+        if !arg.is_empty() && arg.chars().all(safe) {
+            arg.to_string()
+        } else {
+            format!("'{}'", arg.replace('\'', "'\\''"))
+        }
+    };
+    std::iter::once(command).chain(args.iter().copied()).map(quote).collect::<Vec<_>>().join(" ")
+}
+
+fn delete_snapshot_args(snapshot_name: &str) -> [&str; 2] {
+    ["destroy", snapshot_name]
+}
+
+/// The command line `delete_snapshot` runs, to show or copy before confirming
+pub fn delete_snapshot_command(snapshot_name: &str) -> String {
+    command_line("zfs", &delete_snapshot_args(snapshot_name))
+}
+
+pub async fn delete_snapshot(runner: &TokioCommandRunner, snapshot_name: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        log_dry_run("zfs", &delete_snapshot_args(snapshot_name));
+        return Ok(());
+    }
+    if let Some(result) = fixture::with_fixture(|f| f.delete_snapshot(snapshot_name)) {
+        return result;
+    }
+    delete_snapshot_with(runner, snapshot_name).await
+}
+
+pub async fn delete_snapshot_with(runner: &impl CommandRunner, snapshot_name: &str) -> Result<()> {
+    runner
+        .run("zfs", &delete_snapshot_args(snapshot_name))
+        .await
+        .context("Failed to delete ZFS snapshot")?;
+    Ok(())
+}
+
+pub async fn create_dataset(runner: &TokioCommandRunner, dataset_name: &str) -> Result<()> {
+    if let Some(result) = fixture::with_fixture(|f| f.create_dataset(dataset_name)) {
+        return result;
+    }
+    create_dataset_with(runner, dataset_name).await
+}
+
+pub async fn create_dataset_with(runner: &impl CommandRunner, dataset_name: &str) -> Result<()> {
+    runner
+        .run("zfs", &["create", dataset_name])
+        .await
+        .context("Failed to create ZFS dataset")?;
+    Ok(())
+}
+
+/// Destroy a dataset. Without `recursive` ZFS refuses if it has children or snapshots.
+pub async fn destroy_dataset(runner: &TokioCommandRunner, dataset_name: &str, recursive: bool, dry_run: bool) -> Result<()> {
+    if dry_run {
+        log_dry_run("zfs", &destroy_dataset_args(dataset_name, recursive));
+        return Ok(());
+    }
+    if let Some(result) = fixture::with_fixture(|f| f.destroy_dataset(dataset_name, recursive)) {
+        return result;
+    }
+    destroy_dataset_with(runner, dataset_name, recursive).await
+}
+
+fn destroy_dataset_args(dataset_name: &str, recursive: bool) -> Vec<&str> {
+    if recursive {
+        vec!["destroy", "-r", dataset_name]
     } else {
-        None
+        vec!["destroy", dataset_name]
     }
 }
 
+pub async fn destroy_dataset_with(runner: &impl CommandRunner, dataset_name: &str, recursive: bool) -> Result<()> {
+    runner
+        .run("zfs", &destroy_dataset_args(dataset_name, recursive))
+        .await
+        .context("Failed to destroy ZFS dataset")?;
+    Ok(())
+}
+
+pub async fn rename_snapshot(runner: &TokioCommandRunner, old_name: &str, new_name: &str) -> Result<()> {
+    if let Some(result) = fixture::with_fixture(|f| f.rename_snapshot(old_name, new_name)) {
+        return result;
+    }
+    rename_snapshot_with(runner, old_name, new_name).await
+}
 
-pub async fn delete_snapshot(snapshot_name: &str) -> Result<()> {
-    execute_command("zfs", &["destroy", snapshot_name])
+pub async fn rename_snapshot_with(runner: &impl CommandRunner, old_name: &str, new_name: &str) -> Result<()> {
+    runner
+        .run("zfs", &["rename", old_name, new_name])
         .await
-        .context("Failed to delete ZFS snapshot")?;
+        .context("Failed to rename ZFS snapshot")?;
     Ok(())
 }
 
-pub async fn get_snapshots(dataset_name: &str) -> Result<Vec<Snapshot>> {
-    let output = execute_command(
-        "zfs",
-        &["list", "-H", "-p", "-t", "snap", "-r", "-o", "name,used,refer", dataset_name],
-    )
-    .await
-    .with_context(|| format!("Failed to list snapshots for dataset {}", dataset_name))?;
+/// List clones whose `origin` is one of the given snapshots as (clone, origin) pairs; these block
+/// deleting them
+pub async fn snapshot_dependents(runner: &TokioCommandRunner, snapshot_names: &[&str]) -> Result<Vec<(String, String)>> {
+    // Fixtures do not model clones
+    if fixture::is_active() {
+        return Ok(Vec::new());
+    }
+    snapshot_dependents_with(runner, snapshot_names).await
+}
+
+pub async fn snapshot_dependents_with(runner: &impl CommandRunner, snapshot_names: &[&str]) -> Result<Vec<(String, String)>> {
+    if snapshot_names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Clones always live in their origin's pool, so one listing of those pools covers every snapshot
+    let mut pool_names: Vec<&str> = snapshot_names
+        .iter()
+        .map(|name| name.split(['/', '@']).next().unwrap_or(name))
+        .collect();
+    pool_names.sort_unstable();
+    pool_names.dedup();
+
+    let mut args = vec!["list", "-H", "-r", "-t", "filesystem,volume", "-o", "name,origin"];
+    args.extend(&pool_names);
+    let output = runner
+        .run("zfs", &args)
+        .await
+        .context("Failed to check for clones of the snapshots")?;
 
     Ok(output
         .lines()
-        .filter(|line| !line.trim().is_empty())
-        .filter_map(parse_snapshot_line)
+        .filter_map(|line| line.split_once('\t'))
+        .filter(|(_, origin)| snapshot_names.contains(origin))
+        .map(|(name, origin)| (name.to_owned(), origin.to_owned()))
         .collect())
 }
 
+/// Delete each snapshot in turn, returning one result per name in the same order.
+pub async fn delete_snapshots(runner: &TokioCommandRunner, names: &[String], dry_run: bool) -> Vec<Result<()>> {
+    let mut results = Vec::with_capacity(names.len());
+    for name in names {
+        results.push(delete_snapshot(runner, name, dry_run).await);
+    }
+    results
+}
+
+pub async fn get_snapshots(runner: &TokioCommandRunner, dataset_name: &str) -> Result<Listing<Snapshot>> {
+    if let Some(snapshots) = fixture::with_fixture(|f| f.snapshots_in(dataset_name)) {
+        return Ok(Listing::complete(snapshots));
+    }
+    get_snapshots_with(runner, dataset_name).await
+}
+
+pub async fn get_snapshots_with(runner: &impl CommandRunner, dataset_name: &str) -> Result<Listing<Snapshot>> {
+    // Parse rows as they stream in; datasets can have tens of thousands of snapshots
+    let mut snapshots = Listing::new();
+    runner
+        .run_lines(
+            "zfs",
+            &["list", "-H", "-p", "-t", "snap", "-r", "-o", "name,used,refer,creation,written", dataset_name],
+            |line| snapshots.push_line(line, parse_snapshot_line),
+        )
+        .await
+        .with_context(|| format!("Failed to list snapshots for dataset {}", dataset_name))?;
+
+    Ok(snapshots)
+}
+
+/// List the file changes between two snapshots of the same dataset using `zfs diff`.
+///
+/// `zfs diff` needs the dataset mounted and usually root or the `diff` permission.
+pub async fn snapshot_diff(runner: &TokioCommandRunner, older: &str, newer: &str) -> Result<String> {
+    if fixture::is_active() {
+        return Ok(format!("M\t/fixture/changed-file\n(fixture data, no real diff between {} and {})", older, newer));
+    }
+
+    runner
+        .run("zfs", &["diff", "-H", older, newer])
+        .await
+        .with_context(|| format!("Failed to diff {} against {}", newer, older))
+}
+
 fn parse_snapshot_line(line: &str) -> Option<Snapshot> {
-    let fields: Vec<&str> = line.split('\t').collect();
-    if fields.len() >= 3 {
-        Some(Snapshot {
-            name: fields[0].to_owned(),
-            used: parse_u64(fields[1]),
-            referenced: parse_u64(fields[2]),
-        })
-    } else {
-        None
+    let fields = split_fields(line, 5)?;
+    Some(Snapshot {
+        name: fields[0].to_owned(),
+        used: parse_u64(fields[1]).unwrap_or_default(),
+        referenced: parse_u64(fields[2]).unwrap_or_default(),
+        creation: parse_u64(fields[3]),
+        written: parse_u64(fields[4]).unwrap_or_default(),
+    })
+}
+
+fn spawn_error(e: io::Error, command: &str, args: &[&str]) -> anyhow::Error {
+    log::error!("Failed to start {} {}: {}", command, args.join(" "), e);
+    if e.kind() == io::ErrorKind::NotFound {
+        return CommandNotFound { command: command.to_owned() }.into();
+    }
+    anyhow::Error::new(e).context(format!("Failed to execute command: {} {}", command, args.join(" ")))
+}
+
+async fn with_timeout<T>(secs: u64, command: &str, args: &[&str], future: impl Future<Output = Result<T>>) -> Result<T> {
+    if secs == 0 {
+        return future.await;
     }
+    // Dropping the timed-out future kills the child via kill_on_drop
+    tokio::time::timeout(Duration::from_secs(secs), future)
+        .await
+        .unwrap_or_else(|_| {
+            log::error!("Timed out after {}s: {} {}", secs, command, args.join(" "));
+            Err(anyhow!(
+                "Command timed out after {}s: {} {} (is the pool hung?)",
+                secs,
+                command,
+                args.join(" ")
+            ))
+        })
 }
 
-async fn execute_command(command: &str, args: &[&str]) -> Result<String> {
+async fn run_to_completion(command: &str, args: &[&str]) -> Result<String> {
+    log::debug!("Running: {} {}", command, args.join(" "));
+    // Kill the child if the awaiting task is aborted (e.g. a cancelled prefetch)
     let output = Command::new(command)
         .args(args)
+        .kill_on_drop(true)
         .output()
         .await
-        .with_context(|| format!("Failed to execute command: {} {}", command, args.join(" ")))?;
+        .map_err(|e| spawn_error(e, command, args))?;
+    log::debug!("Exited with {}: {} {}", output.status, command, args.join(" "));
 
     if !output.status.success() {
         return Err(anyhow!(
@@ -141,26 +650,428 @@ async fn execute_command(command: &str, args: &[&str]) -> Result<String> {
         .map(|s| s.to_owned())
 }
 
-fn parse_u64(s: &str) -> u64 {
-    s.parse().unwrap_or(0)
+/// Like `run_to_completion`, but reads stdout line by line instead of collecting it first
+async fn stream_lines(command: &str, args: &[&str], mut on_line: impl FnMut(&str)) -> Result<()> {
+    log::debug!("Streaming: {} {}", command, args.join(" "));
+    let mut child = Command::new(command)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| spawn_error(e, command, args))?;
+
+    // Drain stderr concurrently so a chatty command can't block on a full pipe
+    let mut stderr = child.stderr.take().context("Failed to capture command stderr")?;
+    let stderr_task = tokio::spawn(async move {
+        let mut buffer = String::new();
+        let _ = stderr.read_to_string(&mut buffer).await;
+        buffer
+    });
+
+    let stdout = child.stdout.take().context("Failed to capture command stdout")?;
+    let mut lines = BufReader::new(stdout).lines();
+    while let Some(line) = lines.next_line().await.context("Failed to read command output")? {
+        on_line(&line);
+    }
+
+    let status = child.wait().await.context("Failed to wait for command")?;
+    log::debug!("Exited with {}: {} {}", status, command, args.join(" "));
+    if !status.success() {
+        let stderr = stderr_task.await.unwrap_or_default();
+        return Err(anyhow!("Command failed: {} {}\nStderr: {}", command, args.join(" "), stderr));
+    }
+
+    Ok(())
+}
+
+/// Parse a numeric `-p` field, returning None for "-", empty or otherwise non-numeric values
+fn parse_u64(s: &str) -> Option<u64> {
+    s.trim().parse().ok()
+}
+
+/// Parse a size given on the command line: plain bytes, or a number with a 1024-based
+/// suffix as zfs accepts them, e.g. "500M", "1.5G" or "2TiB"
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, suffix) = s.split_at(split);
+    let number: f64 = number.parse().map_err(|_| format!("invalid size '{}'", s))?;
+    let suffix = suffix.trim().to_ascii_uppercase();
+    let suffix = suffix.strip_suffix("IB").or_else(|| suffix.strip_suffix('B')).unwrap_or(&suffix);
+    let exponent = match suffix {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        "P" => 5,
+        _ => return Err(format!("invalid size '{}': unknown unit", s)),
+    };
+    Ok((number * 1024f64.powi(exponent)) as u64)
 }
 
+/// Format a size that may be unknown, showing "-" rather than a misleading 0B
+pub fn format_optional_bytes(bytes: Option<u64>, units: ByteUnits) -> String {
+    bytes.map_or_else(|| "-".to_string(), |bytes| format_bytes(bytes, units))
+}
 
-pub fn format_bytes(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "K", "M", "G", "T", "P"];
-    const THRESHOLD: f64 = 1024.0;
+pub fn format_bytes(bytes: u64, units: ByteUnits) -> String {
+    let (unit_names, threshold): (&[&str], f64) = match units {
+        ByteUnits::Binary => (&["B", "KiB", "MiB", "GiB", "TiB", "PiB"], 1024.0),
+        ByteUnits::Si => (&["B", "KB", "MB", "GB", "TB", "PB"], 1000.0),
+        ByteUnits::Raw => return format!("{}B", bytes),
+    };
 
     let mut size = bytes as f64;
     let mut unit_index = 0;
 
-    while size >= THRESHOLD && unit_index < UNITS.len() - 1 {
-        size /= THRESHOLD;
+    while size >= threshold && unit_index < unit_names.len() - 1 {
+        size /= threshold;
         unit_index += 1;
     }
 
     if unit_index == 0 {
-        format!("{:.0}{}", size, UNITS[unit_index])
+        format!("{:.0}{}", size, unit_names[unit_index])
     } else {
-        format!("{:.1}{}", size, UNITS[unit_index])
+        format!("{:.1}{}", size, unit_names[unit_index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Returns canned output and records every command it is asked to run
+    struct MockCommandRunner {
+        output: String,
+        responses: Vec<(String, String)>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl MockCommandRunner {
+        fn new(output: &str) -> Self {
+            Self {
+                output: output.to_string(),
+                responses: Vec::new(),
+                calls: Mutex::new(Vec::new()),
+            }
+        }
+
+        /// Answer commands starting with `prefix` with `output` instead of the default
+        fn with_response(mut self, prefix: &str, output: &str) -> Self {
+            self.responses.push((prefix.to_string(), output.to_string()));
+            self
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        async fn run(&self, command: &str, args: &[&str]) -> Result<String> {
+            let call = format!("{} {}", command, args.join(" "));
+            let output = self
+                .responses
+                .iter()
+                .find(|(prefix, _)| call.starts_with(prefix.as_str()))
+                .map_or_else(|| self.output.clone(), |(_, output)| output.clone());
+            self.calls.lock().unwrap().push(call);
+            Ok(output)
+        }
+    }
+
+    #[tokio::test]
+    async fn parses_zpool_list_output() {
+        let runner = MockCommandRunner::new(
+            "tank\t4000000000000\t2500000000000\t1500000000000\t12\t62\tONLINE\n\
+             backup\t1000\t0\t1000\t-\t0\tDEGRADED\n",
+        )
+        .with_response("zfs list", "tank\t1800000000000\t900000000000\n");
+
+        let pools = get_pools_with(&runner).await.unwrap().rows;
+
+        assert_eq!(pools.len(), 2);
+        assert_eq!(pools[0].name, "tank");
+        assert_eq!(pools[0].size, 4_000_000_000_000);
+        assert_eq!(pools[0].allocated, 2_500_000_000_000);
+        assert_eq!(pools[0].free, 1_500_000_000_000);
+        assert_eq!(pools[0].fragmentation, Some(12));
+        assert_eq!(pools[0].capacity, 62);
+        assert_eq!(pools[0].health, "ONLINE");
+        assert_eq!(pools[1].fragmentation, None);
+        assert_eq!(pools[1].health, "DEGRADED");
+        assert_eq!(pools[0].usable_used, Some(1_800_000_000_000));
+        assert_eq!(pools[0].usable_size, Some(2_700_000_000_000));
+        assert_eq!(pools[1].usable_size, None);
+        assert_eq!(
+            runner.calls(),
+            [
+                "zpool list -H -p -o name,size,alloc,free,frag,cap,health",
+                "zfs list -H -p -d 0 -o name,used,avail",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn parses_zfs_list_dataset_output() {
+        let runner = MockCommandRunner::new(
+            "tank/home\t800\t200\t800\t0\t0\t/tank/home\tyes\t1000\t5000\ton\t-\t0\t0\n\
+             tank/vol\t50\t0\t50\t0\t10\t-\t-\t60\t5000\t-\ttank/base@gold\t0\t60\n\
+             tank/root\t96\t0\t96\t0\t0\tnone\tno\t96\t5000\toff\t-\t4096\t0\n",
+        );
+
+        let datasets = get_datasets_with(&runner, "tank").await.unwrap().rows;
+
+        assert_eq!(datasets.len(), 3);
+        assert_eq!(datasets[0].name, "tank/home");
+        assert_eq!(datasets[0].referenced, 800);
+        assert_eq!(datasets[0].snapshot_used, Some(200));
+        assert_eq!(datasets[0].mountpoint, "/tank/home");
+        assert!(datasets[0].mounted);
+        assert_eq!(datasets[0].used, 1000);
+        assert_eq!(datasets[0].available, Some(5000));
+        assert_eq!(datasets[1].used_by_refreservation, 10);
+        assert!(!datasets[1].mounted);
+        assert!(!datasets[0].mount_disabled);
+        assert!(datasets[2].mount_disabled);
+        assert_eq!(datasets[0].origin, None);
+        assert_eq!(datasets[1].origin.as_deref(), Some("tank/base@gold"));
+        assert_eq!(datasets[1].refreservation, 60);
+        assert_eq!(datasets[2].reservation, 4096);
+    }
+
+    #[tokio::test]
+    async fn parses_zfs_list_snapshot_output() {
+        let runner = MockCommandRunner::new("tank/home@daily\t120\t750\t1704067200\t300\n\ntank/home@weekly\t80\t790\t1704585600\t40\n");
+
+        let snapshots = get_snapshots_with(&runner, "tank/home").await.unwrap().rows;
+
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].name, "tank/home@daily");
+        assert_eq!(snapshots[0].used, 120);
+        assert_eq!(snapshots[0].referenced, 750);
+        assert_eq!(snapshots[0].creation, Some(1_704_067_200));
+        assert_eq!(snapshots[0].written, 300);
+        assert_eq!(snapshots[1].name, "tank/home@weekly");
+    }
+
+    #[tokio::test]
+    async fn unknown_creation_time_is_not_zero() {
+        let runner = MockCommandRunner::new("tank/home@odd\t120\t750\t-\t300\n");
+
+        let snapshots = get_snapshots_with(&runner, "tank/home").await.unwrap().rows;
+
+        assert_eq!(snapshots[0].creation, None);
+    }
+
+    #[tokio::test]
+    async fn skips_and_records_lines_with_too_few_fields() {
+        let runner = MockCommandRunner::new("tank/home@short-row\t120\n");
+
+        let snapshots = get_snapshots_with(&runner, "tank/home").await.unwrap();
+
+        assert!(snapshots.rows.is_empty());
+        assert_eq!(snapshots.skipped, ["tank/home@short-row\t120"]);
+    }
+
+    #[tokio::test]
+    async fn skipped_rows_belong_to_their_own_listing() {
+        let runner = MockCommandRunner::new("tank\t4000\t2500\t1500\t12\t62\tONLINE\nbroken\n")
+            .with_response("zfs list", "tank\t2400\t1200\n");
+
+        let first = get_pools_with(&runner).await.unwrap();
+        let second = get_pools_with(&runner).await.unwrap();
+
+        assert_eq!(first.rows.len(), 1);
+        assert_eq!(first.skipped, ["broken"]);
+        assert_eq!(second.skipped, ["broken"]);
+    }
+
+    #[tokio::test]
+    async fn tolerates_crlf_and_trailing_empty_fields() {
+        let runner = MockCommandRunner::new(
+            "tank\t4000\t2500\t1500\t12\t62\tONLINE\r\n\
+             backup\t1000\t0\t1000\t-\t0\tDEGRADED\t\r\n",
+        )
+        .with_response("zfs list", "tank/home@daily\t120\t750\t1704067200\t300 \r\ntank/home@weekly\t80\t790\t1704585600\t40\t\t\r");
+
+        let pools = get_pools_with(&runner).await.unwrap().rows;
+        let snapshots = get_snapshots_with(&runner, "tank/home").await.unwrap().rows;
+
+        assert_eq!(pools.len(), 2);
+        assert_eq!(pools[0].health, "ONLINE");
+        assert_eq!(pools[1].health, "DEGRADED");
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].written, 300);
+        assert_eq!(snapshots[1].name, "tank/home@weekly");
+        assert_eq!(snapshots[1].written, 40);
+    }
+
+    #[tokio::test]
+    async fn keeps_names_containing_tabs() {
+        let runner = MockCommandRunner::new("tank/home@odd\tname\t120\t750\t1704067200\t30\n");
+
+        let snapshots = get_snapshots_with(&runner, "tank/home").await.unwrap().rows;
+
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].name, "tank/home@odd\tname");
+        assert_eq!(snapshots[0].used, 120);
+        assert_eq!(snapshots[0].referenced, 750);
+    }
+
+    #[tokio::test]
+    async fn streams_command_output_line_by_line() {
+        let mut lines = Vec::new();
+
+        TokioCommandRunner::default()
+            .run_lines("sh", &["-c", "printf 'first\\nsecond\\n'"], |line| lines.push(line.to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(lines, ["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn runner_kills_commands_that_exceed_its_timeout() {
+        let error = TokioCommandRunner::new(1).run("sleep", &["5"]).await.err().unwrap();
+
+        assert!(format!("{:#}", error).contains("timed out after 1s"));
+        assert!(TokioCommandRunner::new(0).run("true", &[]).await.is_ok());
+    }
+
+    #[test]
+    fn parse_u64_distinguishes_unknown_from_zero() {
+        assert_eq!(parse_u64("0"), Some(0));
+        assert_eq!(parse_u64("12345"), Some(12345));
+        assert_eq!(parse_u64("18446744073709551615"), Some(u64::MAX));
+        assert_eq!(parse_u64("-"), None);
+        assert_eq!(parse_u64(""), None);
+        assert_eq!(parse_u64("none"), None);
+        assert_eq!(parse_u64("1.5G"), None);
+    }
+
+    #[test]
+    fn parse_size_accepts_zfs_style_suffixes() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("500M"), Ok(500 * 1024 * 1024));
+        assert_eq!(parse_size("1.5G"), Ok(1536 * 1024 * 1024));
+        assert_eq!(parse_size("2TiB"), Ok(2 << 40));
+        assert_eq!(parse_size("8kb"), Ok(8192));
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("10X").is_err());
+    }
+
+    #[tokio::test]
+    async fn unset_dataset_fields_are_unknown_not_zero() {
+        let runner = MockCommandRunner::new("tank/vol\t50\t-\t50\t0\t0\t-\t-\t50\t-\t-\t-\t-\t-\n");
+
+        let datasets = get_datasets_with(&runner, "tank").await.unwrap().rows;
+
+        assert_eq!(datasets[0].snapshot_used, None);
+        assert_eq!(datasets[0].available, None);
+        assert_eq!(datasets[0].fullness_percent(), None);
+    }
+
+    #[test]
+    fn format_optional_bytes_shows_dash_for_unknown() {
+        assert_eq!(format_optional_bytes(None, ByteUnits::Binary), "-");
+        assert_eq!(format_optional_bytes(Some(0), ByteUnits::Binary), "0B");
+    }
+
+    #[tokio::test]
+    async fn delete_snapshot_runs_zfs_destroy() {
+        let runner = MockCommandRunner::new("");
+
+        delete_snapshot_with(&runner, "tank/home@daily").await.unwrap();
+
+        assert_eq!(runner.calls(), ["zfs destroy tank/home@daily"]);
+    }
+
+    #[tokio::test]
+    async fn create_dataset_runs_zfs_create() {
+        let runner = MockCommandRunner::new("");
+
+        create_dataset_with(&runner, "tank/home/projects").await.unwrap();
+
+        assert_eq!(runner.calls(), ["zfs create tank/home/projects"]);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn destroy_dataset_only_recurses_when_asked() {
+        let runner = MockCommandRunner::new("");
+
+        destroy_dataset_with(&runner, "tank/scratch", false).await.unwrap();
+        destroy_dataset_with(&runner, "tank/scratch", true).await.unwrap();
+
+        assert_eq!(runner.calls(), ["zfs destroy tank/scratch", "zfs destroy -r tank/scratch"]);
+    }
+
+    #[tokio::test]
+    async fn rename_snapshot_runs_zfs_rename() {
+        let runner = MockCommandRunner::new("");
+
+        rename_snapshot_with(&runner, "tank/home@daily", "tank/home@keep").await.unwrap();
+
+        assert_eq!(runner.calls(), ["zfs rename tank/home@daily tank/home@keep"]);
+    }
+
+    #[tokio::test]
+    async fn pool_iostat_uses_second_sample() {
+        let runner = MockCommandRunner::new(
+            "tank\t100\t900\t5\t6\t1000\t2000\ntank\t100\t900\t50\t60\t10000\t20000\n",
+        );
+
+        let iostat = get_pool_iostat_with(&runner, "tank").await.unwrap();
+
+        assert_eq!(runner.calls(), ["zpool iostat -H -p tank 1 2"]);
+        assert_eq!(
+            iostat,
+            PoolIostat { read_ops: 50, write_ops: 60, read_bytes: 10000, write_bytes: 20000 }
+        );
+    }
+
+    #[tokio::test]
+    async fn snapshot_dependents_lists_only_clones_of_that_snapshot() {
+        let runner = MockCommandRunner::new(
+            "tank\t-\n\
+             tank/home\t-\n\
+             tank/home@daily\t-\n\
+             tank/home@weekly\t-\n\
+             tank/clone-a\ttank/home@daily\n\
+             tank/clone-b\ttank/home@weekly\n\
+             tank/clone-c\ttank/home@daily\n",
+        );
+
+        let dependents = snapshot_dependents_with(&runner, &["tank/home@daily"]).await.unwrap();
+
+        assert_eq!(runner.calls(), ["zfs list -H -r -t filesystem,volume -o name,origin tank"]);
+        assert_eq!(
+            dependents,
+            [
+                ("tank/clone-a".to_string(), "tank/home@daily".to_string()),
+                ("tank/clone-c".to_string(), "tank/home@daily".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn snapshot_dependents_lists_each_pool_once() {
+        let runner = MockCommandRunner::new("tank/clone-a\ttank/home@daily\nbackup/clone-b\tbackup/data@weekly\n");
+
+        let dependents = snapshot_dependents_with(&runner, &["tank/home@daily", "tank/home@hourly", "backup/data@weekly"])
+            .await
+            .unwrap();
+
+        assert_eq!(runner.calls(), ["zfs list -H -r -t filesystem,volume -o name,origin backup tank"]);
+        assert_eq!(dependents.len(), 2);
+    }
+
+    #[test]
+    fn delete_snapshot_command_quotes_unusual_names() {
+        assert_eq!(delete_snapshot_command("tank/home@daily"), "zfs destroy tank/home@daily");
+        assert_eq!(delete_snapshot_command("tank/my data@it's"), "zfs destroy 'tank/my data@it'\\''s'");
+    }
+}